@@ -0,0 +1,41 @@
+DROP INDEX IF EXISTS idx_agent_tx_digest_event_seq;
+
+ALTER TABLE "Agent"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
+
+DROP INDEX IF EXISTS idx_user_tx_digest_event_seq;
+
+ALTER TABLE "User"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
+
+DROP INDEX IF EXISTS idx_buy_offer_tx_digest_event_seq;
+
+ALTER TABLE "BuyOffer"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
+
+DROP INDEX IF EXISTS idx_sell_offer_tx_digest_event_seq;
+
+ALTER TABLE "SellOffer"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
+
+DROP INDEX IF EXISTS idx_manual_buy_tx_digest_event_seq;
+
+ALTER TABLE "ManualBuy"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
+
+DROP INDEX IF EXISTS idx_shop_purchase_tx_digest_event_seq;
+
+ALTER TABLE "ShopPurchase"
+    DROP COLUMN IF EXISTS tx_digest,
+    DROP COLUMN IF EXISTS event_seq,
+    DROP COLUMN IF EXISTS checkpoint;
@@ -0,0 +1,44 @@
+-- Every indexed row records the event it came from. The columns stay nullable
+-- so rows indexed before this migration remain valid.
+
+ALTER TABLE "Agent"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_agent_tx_digest_event_seq ON "Agent"(tx_digest, event_seq);
+
+ALTER TABLE "User"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_tx_digest_event_seq ON "User"(tx_digest, event_seq);
+
+ALTER TABLE "BuyOffer"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_buy_offer_tx_digest_event_seq ON "BuyOffer"(tx_digest, event_seq);
+
+ALTER TABLE "SellOffer"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sell_offer_tx_digest_event_seq ON "SellOffer"(tx_digest, event_seq);
+
+ALTER TABLE "ManualBuy"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_manual_buy_tx_digest_event_seq ON "ManualBuy"(tx_digest, event_seq);
+
+ALTER TABLE "ShopPurchase"
+    ADD COLUMN tx_digest TEXT,
+    ADD COLUMN event_seq BIGINT,
+    ADD COLUMN checkpoint BIGINT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_shop_purchase_tx_digest_event_seq ON "ShopPurchase"(tx_digest, event_seq);
@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS idx_sell_offer_checkpoint_timestamp_ms;
+DROP INDEX IF EXISTS idx_manual_buy_checkpoint_timestamp_ms;
+DROP INDEX IF EXISTS idx_shop_purchase_checkpoint_timestamp_ms;
+
+ALTER TABLE "Agent" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
+ALTER TABLE "User" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
+ALTER TABLE "BuyOffer" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
+ALTER TABLE "SellOffer" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
+ALTER TABLE "ManualBuy" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
+ALTER TABLE "ShopPurchase" DROP COLUMN IF EXISTS checkpoint_timestamp_ms;
@@ -0,0 +1,13 @@
+-- Checkpoint timestamp of the event each row was indexed from. Nothing is
+-- backfilled: rows indexed before this migration keep NULL.
+
+ALTER TABLE "Agent" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+ALTER TABLE "User" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+ALTER TABLE "BuyOffer" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+ALTER TABLE "SellOffer" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+ALTER TABLE "ManualBuy" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+ALTER TABLE "ShopPurchase" ADD COLUMN checkpoint_timestamp_ms BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_sell_offer_checkpoint_timestamp_ms ON "SellOffer"(checkpoint_timestamp_ms);
+CREATE INDEX IF NOT EXISTS idx_manual_buy_checkpoint_timestamp_ms ON "ManualBuy"(checkpoint_timestamp_ms);
+CREATE INDEX IF NOT EXISTS idx_shop_purchase_checkpoint_timestamp_ms ON "ShopPurchase"(checkpoint_timestamp_ms);
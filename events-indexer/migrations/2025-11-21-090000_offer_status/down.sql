@@ -0,0 +1,11 @@
+DROP INDEX IF EXISTS idx_buy_offer_status;
+DROP INDEX IF EXISTS idx_sell_offer_status;
+
+ALTER TABLE "SellOffer"
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS closed_at;
+
+ALTER TABLE "BuyOffer"
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS closed_at,
+    DROP COLUMN IF EXISTS remaining_balance;
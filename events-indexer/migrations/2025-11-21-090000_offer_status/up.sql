@@ -0,0 +1,15 @@
+-- Buy and sell offers are closed instead of deleted so their history is kept.
+-- `closed_at` is the checkpoint at which the offer closed.
+ALTER TABLE "BuyOffer"
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'cancelled', 'fulfilled', 'expired')),
+    ADD COLUMN closed_at BIGINT,
+    ADD COLUMN remaining_balance BIGINT;
+
+ALTER TABLE "SellOffer"
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'closed')),
+    ADD COLUMN closed_at BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_buy_offer_status ON "BuyOffer"(status);
+CREATE INDEX IF NOT EXISTS idx_sell_offer_status ON "SellOffer"(status);
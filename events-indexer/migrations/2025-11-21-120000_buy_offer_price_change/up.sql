@@ -0,0 +1,16 @@
+CREATE TABLE "BuyOfferPriceChange" (
+    id SERIAL PRIMARY KEY,
+    buy_offer_id TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    old_price BIGINT NOT NULL,
+    new_price BIGINT NOT NULL,
+    price_reduction BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    event_seq BIGINT NOT NULL,
+    checkpoint BIGINT NOT NULL,
+    checkpoint_timestamp_ms BIGINT NOT NULL,
+    UNIQUE (tx_digest, event_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_buy_offer_price_change_buy_offer_id ON "BuyOfferPriceChange"(buy_offer_id);
+CREATE INDEX IF NOT EXISTS idx_buy_offer_price_change_owner ON "BuyOfferPriceChange"(owner);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_sell_offer_id;
+CREATE INDEX IF NOT EXISTS idx_sell_offer_id ON "SellOffer"(sell_offer_id);
+
+ALTER TABLE "SellOffer" DROP COLUMN IF EXISTS updated_at;
+
+DROP TABLE IF EXISTS "SellOfferRevision";
@@ -0,0 +1,61 @@
+CREATE TABLE "SellOfferRevision" (
+    id SERIAL PRIMARY KEY,
+    sell_offer_id TEXT NOT NULL,
+    buy_offer_id TEXT NOT NULL,
+    agent_id TEXT NOT NULL,
+    agent_address TEXT NOT NULL,
+    store_link TEXT NOT NULL,
+    price BIGINT NOT NULL,
+    is_update BOOLEAN NOT NULL,
+    tx_digest TEXT NOT NULL,
+    event_seq BIGINT NOT NULL,
+    checkpoint BIGINT NOT NULL,
+    checkpoint_timestamp_ms BIGINT NOT NULL,
+    UNIQUE (tx_digest, event_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sell_offer_revision_sell_offer_id ON "SellOfferRevision"(sell_offer_id);
+CREATE INDEX IF NOT EXISTS idx_sell_offer_revision_buy_offer_id ON "SellOfferRevision"(buy_offer_id);
+
+-- Every SellOffer row indexed so far is one revision.
+INSERT INTO "SellOfferRevision" (
+    sell_offer_id, buy_offer_id, agent_id, agent_address, store_link, price, is_update,
+    tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms
+)
+SELECT
+    sell_offer_id, buy_offer_id, agent_id, agent_address, store_link, price, is_update,
+    tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms
+FROM "SellOffer"
+WHERE tx_digest IS NOT NULL
+  AND event_seq IS NOT NULL
+  AND checkpoint IS NOT NULL
+  AND checkpoint_timestamp_ms IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE "SellOffer" ADD COLUMN updated_at BIGINT;
+UPDATE "SellOffer" SET updated_at = checkpoint;
+
+-- Keep the first row per sell offer, so its id and provenance stay those of the
+-- offer's creation, and apply the latest revision to it the way
+-- `SellOfferMadeHandler` does. Then make `sell_offer_id` the upsert key.
+UPDATE "SellOffer" kept
+SET agent_address = latest.agent_address,
+    store_link = latest.store_link,
+    price = latest.price,
+    is_update = TRUE,
+    updated_at = latest.checkpoint
+FROM (
+    SELECT DISTINCT ON (sell_offer_id) id, sell_offer_id, agent_address, store_link, price, checkpoint
+    FROM "SellOffer"
+    ORDER BY sell_offer_id, id DESC
+) latest
+WHERE kept.sell_offer_id = latest.sell_offer_id
+  AND kept.id < latest.id;
+
+DELETE FROM "SellOffer" newer
+USING "SellOffer" older
+WHERE newer.sell_offer_id = older.sell_offer_id
+  AND newer.id > older.id;
+
+DROP INDEX IF EXISTS idx_sell_offer_id;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sell_offer_id ON "SellOffer"(sell_offer_id);
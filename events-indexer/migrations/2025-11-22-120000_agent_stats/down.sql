@@ -0,0 +1,3 @@
+ALTER TABLE "Agent"
+    DROP COLUMN IF EXISTS total_fees_earned,
+    DROP COLUMN IF EXISTS total_volume;
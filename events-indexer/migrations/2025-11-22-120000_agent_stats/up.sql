@@ -0,0 +1,20 @@
+-- Agent statistics are derived from ShopPurchase, which is emitted for every
+-- completed purchase (manual or not).
+ALTER TABLE "Agent"
+    ADD COLUMN total_fees_earned BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN total_volume BIGINT NOT NULL DEFAULT 0;
+
+UPDATE "Agent" SET
+    buys = stats.buys,
+    total_fees_earned = stats.total_fees_earned,
+    total_volume = stats.total_volume
+FROM (
+    SELECT
+        agent_id,
+        COUNT(*) AS buys,
+        SUM(agent_fee)::BIGINT AS total_fees_earned,
+        SUM(product_price)::BIGINT AS total_volume
+    FROM "ShopPurchase"
+    GROUP BY agent_id
+) stats
+WHERE "Agent".agent_id = stats.agent_id;
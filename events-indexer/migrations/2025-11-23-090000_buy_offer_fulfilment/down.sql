@@ -0,0 +1,10 @@
+UPDATE "SellOffer" SET status = 'closed' WHERE status IN ('accepted', 'rejected');
+
+ALTER TABLE "SellOffer" DROP CONSTRAINT IF EXISTS "SellOffer_status_check";
+ALTER TABLE "SellOffer" ADD CONSTRAINT "SellOffer_status_check"
+    CHECK (status IN ('open', 'closed'));
+
+ALTER TABLE "BuyOffer"
+    DROP COLUMN IF EXISTS accepted_sell_offer_id,
+    DROP COLUMN IF EXISTS total_paid,
+    DROP COLUMN IF EXISTS filled_at;
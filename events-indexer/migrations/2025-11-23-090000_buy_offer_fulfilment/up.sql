@@ -0,0 +1,24 @@
+-- A ManualBuy fulfils its buy offer: record the accepted sell offer, the amount
+-- paid and when (checkpoint timestamp), and settle the competing sell offers.
+ALTER TABLE "BuyOffer"
+    ADD COLUMN accepted_sell_offer_id TEXT,
+    ADD COLUMN total_paid BIGINT,
+    ADD COLUMN filled_at BIGINT;
+
+ALTER TABLE "SellOffer" DROP CONSTRAINT IF EXISTS "SellOffer_status_check";
+ALTER TABLE "SellOffer" ADD CONSTRAINT "SellOffer_status_check"
+    CHECK (status IN ('open', 'closed', 'accepted', 'rejected'));
+
+-- Apply the same transition to manual buys indexed before this migration.
+UPDATE "BuyOffer" SET
+    status = 'fulfilled',
+    accepted_sell_offer_id = mb.sell_offer_id,
+    total_paid = mb.total_paid,
+    filled_at = mb.checkpoint_timestamp_ms
+FROM "ManualBuy" mb
+WHERE "BuyOffer".buy_offer_id = mb.buy_offer_id;
+
+UPDATE "SellOffer" SET
+    status = CASE WHEN "SellOffer".sell_offer_id = mb.sell_offer_id THEN 'accepted' ELSE 'rejected' END
+FROM "ManualBuy" mb
+WHERE "SellOffer".buy_offer_id = mb.buy_offer_id;
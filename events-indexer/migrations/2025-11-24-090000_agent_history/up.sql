@@ -0,0 +1,34 @@
+-- One row per change to an agent's stake, activity or rating. "Agent" holds
+-- the latest values, recomputed from this table.
+CREATE TABLE "AgentHistory" (
+    id SERIAL PRIMARY KEY,
+    agent_id TEXT NOT NULL,
+    change TEXT NOT NULL CHECK (change IN (
+        'registered', 'staked', 'unstaked', 'slashed', 'deactivated', 'reactivated', 'rating_updated'
+    )),
+    stake_delta BIGINT,
+    stake_amount BIGINT,
+    active BOOLEAN,
+    rating BIGINT,
+    changed_at BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    event_seq BIGINT NOT NULL,
+    checkpoint BIGINT NOT NULL,
+    checkpoint_timestamp_ms BIGINT NOT NULL,
+    UNIQUE (tx_digest, event_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_agent_history_agent_id ON "AgentHistory"(agent_id, checkpoint);
+
+-- Registrations indexed before provenance was recorded cannot be keyed and are
+-- left out; their "Agent" row stays the source of truth until a later change.
+INSERT INTO "AgentHistory" (
+    agent_id, change, stake_delta, stake_amount, active, rating, changed_at,
+    tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms
+)
+SELECT
+    agent_id, 'registered', stake_amount, stake_amount, active, rating, registered_at,
+    tx_digest, event_seq, checkpoint, COALESCE(checkpoint_timestamp_ms, registered_at)
+FROM "Agent"
+WHERE tx_digest IS NOT NULL AND event_seq IS NOT NULL AND checkpoint IS NOT NULL
+ORDER BY checkpoint, id;
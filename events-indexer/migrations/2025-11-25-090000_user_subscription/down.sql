@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_user_active_subscription_deadline;
+
+ALTER TABLE "User"
+    DROP COLUMN IF EXISTS subscription_cancelled_at,
+    DROP COLUMN IF EXISTS updated_at;
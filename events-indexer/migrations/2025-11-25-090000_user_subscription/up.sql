@@ -0,0 +1,9 @@
+-- Subscriptions change after registration through renewal and cancellation
+-- events; `updated_at` is the checkpoint of the latest one applied, so replaying
+-- an older event cannot roll a subscription back.
+ALTER TABLE "User"
+    ADD COLUMN subscription_cancelled_at BIGINT,
+    ADD COLUMN updated_at BIGINT;
+
+-- Expiry is swept on every checkpoint against the active users.
+CREATE INDEX IF NOT EXISTS idx_user_active_subscription_deadline ON "User"(active, subscription_deadline);
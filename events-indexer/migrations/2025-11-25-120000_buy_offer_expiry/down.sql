@@ -0,0 +1 @@
+DROP INDEX IF EXISTS idx_buy_offer_open_deadline;
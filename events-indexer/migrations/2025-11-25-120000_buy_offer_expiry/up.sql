@@ -0,0 +1,4 @@
+-- Time-based buy offers still open are checked against every processed
+-- checkpoint's timestamp.
+CREATE INDEX IF NOT EXISTS idx_buy_offer_open_deadline ON "BuyOffer"(deadline)
+    WHERE status = 'open' AND offer_type_is_time_based;
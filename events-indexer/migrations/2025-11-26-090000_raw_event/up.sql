@@ -0,0 +1,22 @@
+-- Every event emitted by a configured PriceLess package, whether or not it is
+-- projected into a typed table. `bcs` holds the contents as emitted and `json`
+-- their decoded form when a handler knows the type.
+CREATE TABLE "RawEvent" (
+    id BIGSERIAL PRIMARY KEY,
+    package_id TEXT NOT NULL,
+    module TEXT NOT NULL,
+    struct_name TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    event_seq BIGINT NOT NULL,
+    sender TEXT NOT NULL,
+    checkpoint BIGINT NOT NULL,
+    checkpoint_timestamp_ms BIGINT NOT NULL,
+    bcs BYTEA NOT NULL,
+    json JSONB,
+    UNIQUE (tx_digest, event_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_raw_event_checkpoint ON "RawEvent"(checkpoint);
+CREATE INDEX IF NOT EXISTS idx_raw_event_struct_name ON "RawEvent"(module, struct_name, checkpoint);
+CREATE INDEX IF NOT EXISTS idx_raw_event_sender ON "RawEvent"(sender);
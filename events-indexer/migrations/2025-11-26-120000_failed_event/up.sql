@@ -0,0 +1,23 @@
+-- Events that could not be decoded while indexing with the dead-letter policy.
+-- `resolved_at` is set once `retry-failed` decodes and projects the event.
+CREATE TABLE "FailedEvent" (
+    id BIGSERIAL PRIMARY KEY,
+    package_id TEXT NOT NULL,
+    module TEXT NOT NULL,
+    struct_name TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    event_seq BIGINT NOT NULL,
+    sender TEXT NOT NULL,
+    checkpoint BIGINT NOT NULL,
+    checkpoint_timestamp_ms BIGINT NOT NULL,
+    bcs BYTEA NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    failed_at TIMESTAMP NOT NULL DEFAULT now(),
+    resolved_at TIMESTAMP,
+    UNIQUE (tx_digest, event_seq)
+);
+
+CREATE INDEX IF NOT EXISTS idx_failed_event_unresolved ON "FailedEvent"(checkpoint, id)
+    WHERE resolved_at IS NULL;
@@ -0,0 +1,47 @@
+-- Fails if any value no longer fits a BIGINT.
+ALTER TABLE "Agent"
+    ALTER COLUMN stake_amount TYPE BIGINT,
+    ALTER COLUMN rating TYPE BIGINT,
+    ALTER COLUMN registered_at TYPE BIGINT,
+    ALTER COLUMN total_fees_earned TYPE BIGINT,
+    ALTER COLUMN total_volume TYPE BIGINT;
+
+ALTER TABLE "AgentHistory"
+    ALTER COLUMN stake_delta TYPE BIGINT,
+    ALTER COLUMN stake_amount TYPE BIGINT,
+    ALTER COLUMN rating TYPE BIGINT,
+    ALTER COLUMN changed_at TYPE BIGINT;
+
+ALTER TABLE "BuyOffer"
+    ALTER COLUMN price TYPE BIGINT,
+    ALTER COLUMN deadline TYPE BIGINT,
+    ALTER COLUMN created_at TYPE BIGINT,
+    ALTER COLUMN remaining_balance TYPE BIGINT,
+    ALTER COLUMN total_paid TYPE BIGINT;
+
+ALTER TABLE "BuyOfferPriceChange"
+    ALTER COLUMN old_price TYPE BIGINT,
+    ALTER COLUMN new_price TYPE BIGINT,
+    ALTER COLUMN price_reduction TYPE BIGINT;
+
+ALTER TABLE "ManualBuy"
+    ALTER COLUMN product_price TYPE BIGINT,
+    ALTER COLUMN agent_fee TYPE BIGINT,
+    ALTER COLUMN total_paid TYPE BIGINT;
+
+ALTER TABLE "SellOffer"
+    ALTER COLUMN price TYPE BIGINT;
+
+ALTER TABLE "SellOfferRevision"
+    ALTER COLUMN price TYPE BIGINT;
+
+ALTER TABLE "ShopPurchase"
+    ALTER COLUMN product_price TYPE BIGINT,
+    ALTER COLUMN agent_fee TYPE BIGINT,
+    ALTER COLUMN platform_fee TYPE BIGINT;
+
+ALTER TABLE "User"
+    ALTER COLUMN subscription_fee TYPE BIGINT,
+    ALTER COLUMN subscription_deadline TYPE BIGINT,
+    ALTER COLUMN registered_at TYPE BIGINT,
+    ALTER COLUMN subscription_cancelled_at TYPE BIGINT;
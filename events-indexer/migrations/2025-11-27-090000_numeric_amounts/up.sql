@@ -0,0 +1,51 @@
+-- On-chain u64 values (amounts, ratings, deadlines and Move timestamps) no
+-- longer fit BIGINT in general, e.g. u64::MAX used as "no deadline". Store them
+-- as NUMERIC(20,0), which holds every u64 exactly. Running totals may exceed a
+-- single u64 and get NUMERIC(39,0).
+
+ALTER TABLE "Agent"
+    ALTER COLUMN stake_amount TYPE NUMERIC(20,0),
+    ALTER COLUMN rating TYPE NUMERIC(20,0),
+    ALTER COLUMN registered_at TYPE NUMERIC(20,0),
+    ALTER COLUMN total_fees_earned TYPE NUMERIC(39,0),
+    ALTER COLUMN total_volume TYPE NUMERIC(39,0);
+
+ALTER TABLE "AgentHistory"
+    ALTER COLUMN stake_delta TYPE NUMERIC(20,0),
+    ALTER COLUMN stake_amount TYPE NUMERIC(20,0),
+    ALTER COLUMN rating TYPE NUMERIC(20,0),
+    ALTER COLUMN changed_at TYPE NUMERIC(20,0);
+
+ALTER TABLE "BuyOffer"
+    ALTER COLUMN price TYPE NUMERIC(20,0),
+    ALTER COLUMN deadline TYPE NUMERIC(20,0),
+    ALTER COLUMN created_at TYPE NUMERIC(20,0),
+    ALTER COLUMN remaining_balance TYPE NUMERIC(20,0),
+    ALTER COLUMN total_paid TYPE NUMERIC(20,0);
+
+ALTER TABLE "BuyOfferPriceChange"
+    ALTER COLUMN old_price TYPE NUMERIC(20,0),
+    ALTER COLUMN new_price TYPE NUMERIC(20,0),
+    ALTER COLUMN price_reduction TYPE NUMERIC(20,0);
+
+ALTER TABLE "ManualBuy"
+    ALTER COLUMN product_price TYPE NUMERIC(20,0),
+    ALTER COLUMN agent_fee TYPE NUMERIC(20,0),
+    ALTER COLUMN total_paid TYPE NUMERIC(20,0);
+
+ALTER TABLE "SellOffer"
+    ALTER COLUMN price TYPE NUMERIC(20,0);
+
+ALTER TABLE "SellOfferRevision"
+    ALTER COLUMN price TYPE NUMERIC(20,0);
+
+ALTER TABLE "ShopPurchase"
+    ALTER COLUMN product_price TYPE NUMERIC(20,0),
+    ALTER COLUMN agent_fee TYPE NUMERIC(20,0),
+    ALTER COLUMN platform_fee TYPE NUMERIC(20,0);
+
+ALTER TABLE "User"
+    ALTER COLUMN subscription_fee TYPE NUMERIC(20,0),
+    ALTER COLUMN subscription_deadline TYPE NUMERIC(20,0),
+    ALTER COLUMN registered_at TYPE NUMERIC(20,0),
+    ALTER COLUMN subscription_cancelled_at TYPE NUMERIC(20,0);
@@ -0,0 +1,22 @@
+-- Resume the single `events` pipeline from the pipeline furthest behind.
+DO $$
+BEGIN
+    IF to_regclass('watermarks') IS NOT NULL THEN
+        INSERT INTO watermarks (
+            pipeline, epoch_hi_inclusive, checkpoint_hi_inclusive, tx_hi,
+            timestamp_ms_hi_inclusive, reader_lo, pruner_timestamp, pruner_hi
+        )
+        SELECT
+            'events', w.epoch_hi_inclusive, w.checkpoint_hi_inclusive, w.tx_hi,
+            w.timestamp_ms_hi_inclusive, w.reader_lo, w.pruner_timestamp, w.pruner_hi
+        FROM watermarks w
+        WHERE w.pipeline IN ('raw_events', 'offers', 'agents', 'users', 'manual_buys', 'shop_purchases')
+        ORDER BY w.checkpoint_hi_inclusive
+        LIMIT 1
+        ON CONFLICT (pipeline) DO NOTHING;
+
+        DELETE FROM watermarks
+        WHERE pipeline IN ('raw_events', 'offers', 'agents', 'users', 'manual_buys', 'shop_purchases');
+    END IF;
+END
+$$;
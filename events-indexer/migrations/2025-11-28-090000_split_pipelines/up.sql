@@ -0,0 +1,24 @@
+-- The single `events` pipeline is split into one pipeline per group of tables.
+-- Each new pipeline resumes from where `events` stopped. The framework creates
+-- `watermarks`, so there is nothing to carry over on a fresh database.
+DO $$
+BEGIN
+    IF to_regclass('watermarks') IS NOT NULL THEN
+        INSERT INTO watermarks (
+            pipeline, epoch_hi_inclusive, checkpoint_hi_inclusive, tx_hi,
+            timestamp_ms_hi_inclusive, reader_lo, pruner_timestamp, pruner_hi
+        )
+        SELECT
+            p.name, w.epoch_hi_inclusive, w.checkpoint_hi_inclusive, w.tx_hi,
+            w.timestamp_ms_hi_inclusive, w.reader_lo, w.pruner_timestamp, w.pruner_hi
+        FROM watermarks w
+        CROSS JOIN (VALUES
+            ('raw_events'), ('offers'), ('agents'), ('users'), ('manual_buys'), ('shop_purchases')
+        ) AS p(name)
+        WHERE w.pipeline = 'events'
+        ON CONFLICT (pipeline) DO NOTHING;
+
+        DELETE FROM watermarks WHERE pipeline = 'events';
+    END IF;
+END
+$$;
@@ -0,0 +1,10 @@
+ALTER TABLE "SellOffer" DROP CONSTRAINT IF EXISTS fk_sell_offer_buy_offer;
+
+DROP INDEX IF EXISTS idx_buy_offer_id;
+CREATE INDEX IF NOT EXISTS idx_buy_offer_id ON "BuyOffer"(buy_offer_id);
+
+DROP INDEX IF EXISTS idx_user_user_owner_address;
+CREATE INDEX IF NOT EXISTS idx_user_user_owner_address ON "User"(user_owner_address);
+
+DROP INDEX IF EXISTS idx_agent_agent_id;
+CREATE INDEX IF NOT EXISTS idx_agent_agent_id ON "Agent"(agent_id);
@@ -0,0 +1,45 @@
+-- Referenced ids are unique on chain; copies can only come from replays
+-- indexed before provenance was recorded. Keep the first row of each, then
+-- make the id a key other tables can reference.
+DELETE FROM "Agent" later
+USING "Agent" earlier
+WHERE later.agent_id = earlier.agent_id
+  AND later.id > earlier.id;
+
+DELETE FROM "User" later
+USING "User" earlier
+WHERE later.user_owner_address = earlier.user_owner_address
+  AND later.id > earlier.id;
+
+DELETE FROM "BuyOffer" later
+USING "BuyOffer" earlier
+WHERE later.buy_offer_id = earlier.buy_offer_id
+  AND later.id > earlier.id;
+
+DROP INDEX IF EXISTS idx_agent_agent_id;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_agent_agent_id ON "Agent"(agent_id);
+
+-- The platform registry allows one user per owner address.
+DROP INDEX IF EXISTS idx_user_user_owner_address;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_user_owner_address ON "User"(user_owner_address);
+
+DROP INDEX IF EXISTS idx_buy_offer_id;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_buy_offer_id ON "BuyOffer"(buy_offer_id);
+
+-- Only references between rows of the same pipeline are enforced: a sell
+-- offer's buy offer is written by the offers pipeline, from an event that
+-- always comes first. Checked when the writing transaction commits, so the
+-- rows of a batch can be inserted in any order. NOT VALID skips the rows
+-- indexed so far, which may reference events from before the first indexed
+-- checkpoint. A sell offer whose buy offer was never indexed is skipped by
+-- the indexer rather than left to fail this check.
+--
+-- The other references are out of scope: SellOffer.agent_id and
+-- ManualBuy.agent_id point into "Agent" (agents pipeline), ManualBuy.buy_offer_id
+-- and ManualBuy.sell_offer_id are written by the manual_buys pipeline. Each
+-- pipeline commits on its own watermark, so the referent is routinely
+-- committed after the referencing row, even deferred to the end of the
+-- transaction. The `orphans` subcommand lists the rows missing one.
+ALTER TABLE "SellOffer" ADD CONSTRAINT fk_sell_offer_buy_offer
+    FOREIGN KEY (buy_offer_id) REFERENCES "BuyOffer"(buy_offer_id)
+    DEFERRABLE INITIALLY DEFERRED NOT VALID;
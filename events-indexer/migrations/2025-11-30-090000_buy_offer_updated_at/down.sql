@@ -0,0 +1 @@
+ALTER TABLE "BuyOffer" DROP COLUMN IF EXISTS updated_at;
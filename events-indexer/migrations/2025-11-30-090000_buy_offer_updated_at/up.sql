@@ -0,0 +1,11 @@
+-- `updated_at` is the checkpoint of the latest price change applied to the
+-- offer, so replaying an older `BuyOfferModified` cannot roll the price back.
+ALTER TABLE "BuyOffer" ADD COLUMN updated_at BIGINT;
+
+UPDATE "BuyOffer" SET updated_at = latest.checkpoint
+FROM (
+    SELECT buy_offer_id, MAX(checkpoint) AS checkpoint
+    FROM "BuyOfferPriceChange"
+    GROUP BY buy_offer_id
+) latest
+WHERE "BuyOffer".buy_offer_id = latest.buy_offer_id;
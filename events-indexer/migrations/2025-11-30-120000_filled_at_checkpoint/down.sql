@@ -0,0 +1,4 @@
+UPDATE "BuyOffer" SET filled_at = mb.checkpoint_timestamp_ms
+FROM "ManualBuy" mb
+WHERE "BuyOffer".buy_offer_id = mb.buy_offer_id
+  AND "BuyOffer".filled_at IS NOT NULL;
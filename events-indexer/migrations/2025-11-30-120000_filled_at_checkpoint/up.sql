@@ -0,0 +1,6 @@
+-- `filled_at` was the checkpoint timestamp in milliseconds. Make it the
+-- checkpoint number, like every other `*_at` column of the offers.
+UPDATE "BuyOffer" SET filled_at = mb.checkpoint
+FROM "ManualBuy" mb
+WHERE "BuyOffer".buy_offer_id = mb.buy_offer_id
+  AND "BuyOffer".filled_at IS NOT NULL;
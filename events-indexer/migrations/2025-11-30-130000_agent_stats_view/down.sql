@@ -0,0 +1,21 @@
+DROP VIEW IF EXISTS "AgentWithStats";
+
+ALTER TABLE "Agent"
+    ADD COLUMN buys BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN total_fees_earned NUMERIC(39,0) NOT NULL DEFAULT 0,
+    ADD COLUMN total_volume NUMERIC(39,0) NOT NULL DEFAULT 0;
+
+UPDATE "Agent" SET
+    buys = stats.buys,
+    total_fees_earned = stats.total_fees_earned,
+    total_volume = stats.total_volume
+FROM (
+    SELECT
+        agent_id,
+        COUNT(*) AS buys,
+        SUM(agent_fee) AS total_fees_earned,
+        SUM(product_price) AS total_volume
+    FROM "ShopPurchase"
+    GROUP BY agent_id
+) stats
+WHERE "Agent".agent_id = stats.agent_id;
@@ -0,0 +1,23 @@
+-- Agent statistics were written into "Agent" by the shop_purchases pipeline,
+-- although "Agent" belongs to the agents pipeline. Compute them from the
+-- purchases indexed so far whenever agents are read instead.
+ALTER TABLE "Agent"
+    DROP COLUMN buys,
+    DROP COLUMN total_fees_earned,
+    DROP COLUMN total_volume;
+
+CREATE VIEW "AgentWithStats" AS
+SELECT
+    a.*,
+    stats.buys,
+    stats.total_fees_earned,
+    stats.total_volume
+FROM "Agent" a
+CROSS JOIN LATERAL (
+    SELECT
+        COUNT(*) AS buys,
+        COALESCE(SUM(p.agent_fee), 0)::NUMERIC(39,0) AS total_fees_earned,
+        COALESCE(SUM(p.product_price), 0)::NUMERIC(39,0) AS total_volume
+    FROM "ShopPurchase" p
+    WHERE p.agent_id = a.agent_id
+) stats;
@@ -1,28 +1,201 @@
+use anyhow::{bail, Context, Result};
 use clap::ValueEnum;
-use std::env;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use sui_indexer_alt_framework::pipeline::concurrent::ConcurrentConfig;
+use sui_indexer_alt_framework::pipeline::sequential::SequentialConfig;
+use sui_indexer_alt_framework::pipeline::CommitterConfig;
+use sui_indexer_alt_framework::postgres::DbArgs;
+use sui_types::base_types::ObjectID;
+use url::Url;
 
-#[derive(Debug, Clone, ValueEnum)]
+/// Rating given to newly registered agents unless configured otherwise.
+pub const DEFAULT_AGENT_RATING: u64 = 500;
+
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Network {
+    Mainnet,
     Testnet,
+    Devnet,
+    Localnet,
+}
+
+/// What to do with an event from a configured package that fails to decode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecodeErrorPolicy {
+    /// Stop the pipeline until the event can be decoded.
+    #[default]
+    Fail,
+    /// Log the error and carry on; the event is still archived in `RawEvent`.
+    Skip,
+    /// Carry on, recording the event and the error in `FailedEvent` for
+    /// `retry-failed`.
+    DeadLetter,
+}
+
+/// Built-in settings for a network. Each one can be overridden from the
+/// environment or the command line.
+#[derive(Debug, Clone)]
+pub struct NetworkDefaults {
+    /// PriceLess package versions, in the format accepted by [`PackageConfig::parse`].
+    pub package_versions: Option<&'static str>,
+    /// Remote checkpoint store to ingest from.
+    pub remote_store_url: Option<&'static str>,
+    /// Directory of checkpoint files to ingest from, used instead of a remote store.
+    pub local_ingestion_path: Option<&'static str>,
+    /// Checkpoint to start from when neither `--first-checkpoint` nor an
+    /// `@<checkpoint>` in the package versions gives one. `None` where the
+    /// package's publish checkpoint is not known; the indexer then refuses to
+    /// start until one is given.
+    pub first_checkpoint: Option<u64>,
+}
+
+impl Network {
+    pub fn defaults(&self) -> NetworkDefaults {
+        match self {
+            // PriceLess is not published on mainnet or devnet: neither the package
+            // nor its publish checkpoint is known.
+            Network::Mainnet => NetworkDefaults {
+                package_versions: None,
+                remote_store_url: Some("https://checkpoints.mainnet.sui.io"),
+                local_ingestion_path: None,
+                first_checkpoint: None,
+            },
+            Network::Testnet => NetworkDefaults {
+                package_versions: Some(
+                    "0xfba4cc87e3422f056280e309a485dd6a858196444fa983add6666ba5209cd3ff,\
+                     0xf204f9d6836cdbf4717a8e9434c91f940a195e0aaef734b2d1a350e058ecac2e",
+                ),
+                remote_store_url: Some("https://checkpoints.testnet.sui.io"),
+                local_ingestion_path: None,
+                // Neither `Move.lock` nor the deployment notes record the checkpoint the
+                // package was published at.
+                first_checkpoint: None,
+            },
+            Network::Devnet => NetworkDefaults {
+                package_versions: None,
+                remote_store_url: Some("https://checkpoints.devnet.sui.io"),
+                local_ingestion_path: None,
+                first_checkpoint: None,
+            },
+            // `sui start --data-ingestion-dir ./checkpoints` writes checkpoints here.
+            Network::Localnet => NetworkDefaults {
+                package_versions: None,
+                remote_store_url: None,
+                local_ingestion_path: Some("./checkpoints"),
+                // A fresh `--force-regenesis` network publishes the package near genesis.
+                first_checkpoint: Some(0),
+            },
+        }
+    }
+}
+
+/// One published version of the PriceLess package. Event types keep the id of
+/// the version that introduced them, so every version must be tracked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageVersion {
+    pub package_id: ObjectID,
+    /// First checkpoint at which this version could have emitted events.
+    pub first_checkpoint: u64,
+}
+
+impl fmt::Display for PackageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.package_id, self.first_checkpoint)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct PackageConfig {
-    pub agent_package_id: String,
+    /// The original package first, followed by each upgrade in publish order.
+    pub versions: Vec<PackageVersion>,
 }
 
 impl PackageConfig {
-    pub fn for_network(network: Network) -> Self {
-        match network {
-            Network::Testnet => Self::testnet(),
+    /// Package versions for `network`. `versions` (from `--package-versions`,
+    /// `PACKAGE_VERSIONS` or `PACKAGE_ID`) takes precedence over the network's
+    /// built-in default.
+    pub fn for_network(network: &Network, versions: Option<&str>) -> Result<Self> {
+        if let Some(versions) = versions {
+            return Self::parse(versions).context("Invalid package versions");
         }
+
+        let Some(defaults) = network.defaults().package_versions else {
+            bail!(
+                "No PriceLess package is known for {network:?}; set PACKAGE_ID, \
+                 PACKAGE_VERSIONS or --package-versions"
+            );
+        };
+
+        Self::parse(defaults)
     }
 
-    fn testnet() -> Self {
-        Self {
-            agent_package_id: env::var("PACKAGE_ID")
-                .expect("PACKAGE_ID must be set in environment"),
+    /// Parse a comma-separated list of `<package id>[@<first checkpoint>]`
+    /// entries, e.g. `0xabc,0xdef@1200000`. The original package defaults to
+    /// checkpoint 0.
+    pub fn parse(versions: &str) -> Result<Self> {
+        let mut parsed: Vec<PackageVersion> = Vec::new();
+
+        for entry in versions.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, first_checkpoint) = match entry.split_once('@') {
+                Some((id, checkpoint)) => (
+                    id,
+                    checkpoint
+                        .parse()
+                        .with_context(|| format!("Invalid checkpoint in '{entry}'"))?,
+                ),
+                None => (entry, 0),
+            };
+
+            let package_id = ObjectID::from_hex_literal(id)
+                .with_context(|| format!("'{id}' is not a valid Sui object ID"))?;
+
+            if parsed.iter().any(|v| v.package_id == package_id) {
+                bail!("Package {package_id} is listed more than once");
+            }
+
+            if let Some(previous) = parsed.last() {
+                if first_checkpoint < previous.first_checkpoint {
+                    bail!(
+                        "Package versions must be ordered by first checkpoint, but {package_id} \
+                         starts at {first_checkpoint}, before {}",
+                        previous.first_checkpoint
+                    );
+                }
+            }
+
+            parsed.push(PackageVersion {
+                package_id,
+                first_checkpoint,
+            });
         }
+
+        if parsed.is_empty() {
+            bail!("At least one package id is required");
+        }
+
+        Ok(Self { versions: parsed })
+    }
+
+    /// Earliest checkpoint any configured version can have emitted events at.
+    pub fn first_checkpoint(&self) -> u64 {
+        self.versions
+            .iter()
+            .map(|v| v.first_checkpoint)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// The version `package_id` belongs to, if it is known and active at `checkpoint`.
+    pub fn version_at(&self, package_id: &ObjectID, checkpoint: u64) -> Option<&PackageVersion> {
+        self.versions
+            .iter()
+            .find(|v| &v.package_id == package_id && v.first_checkpoint <= checkpoint)
     }
 }
 
@@ -30,4 +203,371 @@ impl Default for Network {
     fn default() -> Self {
         Network::Testnet
     }
-}
\ No newline at end of file
+}
+
+// ============== CONFIGURATION FILE ==============
+
+/// Contents of the `--config` TOML file. Every setting is optional: values
+/// from the command line win over the environment, which wins over this file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    pub network: Option<Network>,
+
+    /// PriceLess package versions, original first, as `<id>[@<first checkpoint>]`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<String>,
+
+    pub database: DatabaseConfig,
+
+    pub ingestion: IngestionConfig,
+
+    /// Tuning for each pipeline, keyed by pipeline name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub pipelines: BTreeMap<String, PipelineLayer>,
+
+    pub events: EventsConfig,
+
+    pub defaults: DefaultsConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: Option<Url>,
+
+    /// PEM contents of the CA certificate used to verify the database.
+    pub tls_ca_cert: Option<String>,
+}
+
+impl DatabaseConfig {
+    /// `base` with TLS verification against the configured CA certificate, if
+    /// any, which is written to disk for the connection pool to read.
+    pub fn db_args(&self, base: DbArgs) -> Result<DbArgs> {
+        let Some(cert_content) = self.tls_ca_cert.as_deref().filter(|c| !c.is_empty()) else {
+            return Ok(base);
+        };
+
+        let cert_dir = PathBuf::from("./certificates");
+        fs::create_dir_all(&cert_dir)?;
+
+        let cert_path = cert_dir.join("ca-cert.crt");
+        fs::write(&cert_path, cert_content)?;
+
+        Ok(DbArgs {
+            tls_verify_cert: true,
+            tls_ca_cert_path: Some(cert_path),
+            ..base
+        })
+    }
+}
+
+/// Where checkpoints are read from and where indexing starts. Only one source
+/// is used; whatever is unset comes from the network's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IngestionConfig {
+    pub remote_store_url: Option<Url>,
+    pub local_ingestion_path: Option<PathBuf>,
+    pub rpc_api_url: Option<Url>,
+    pub first_checkpoint: Option<u64>,
+}
+
+/// Which event types are indexed. With `enabled` set, only those are indexed;
+/// `disabled` is then applied on top.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EventsConfig {
+    pub enabled: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+
+    pub on_decode_error: DecodeErrorPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefaultsConfig {
+    /// Rating assigned to an agent when its registration is indexed.
+    pub agent_rating: u64,
+}
+
+/// Overrides for a pipeline's `SequentialConfig` or `ConcurrentConfig`. Unset
+/// fields keep the framework's defaults; `checkpoint_lag` only applies to
+/// sequential pipelines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PipelineLayer {
+    pub write_concurrency: Option<usize>,
+    pub collect_interval_ms: Option<u64>,
+    pub watermark_interval_ms: Option<u64>,
+    pub checkpoint_lag: Option<u64>,
+}
+
+impl IndexerConfig {
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    /// Settings for the pipeline called `name`, falling back to the framework's
+    /// defaults for anything not configured.
+    pub fn sequential_config(&self, name: &str) -> SequentialConfig {
+        self.pipelines
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+            .finish(SequentialConfig::default())
+    }
+
+    /// Settings for the concurrent pipeline called `name`, falling back to the
+    /// framework's defaults for anything not configured.
+    pub fn concurrent_config(&self, name: &str) -> ConcurrentConfig {
+        self.pipelines
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+            .finish_concurrent(ConcurrentConfig::default())
+    }
+
+    /// Render as TOML with the database password masked.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut redacted = self.clone();
+
+        if let Some(url) = redacted.database.url.as_mut() {
+            if url.password().is_some() {
+                let _ = url.set_password(Some("********"));
+            }
+        }
+
+        if redacted.database.tls_ca_cert.is_some() {
+            redacted.database.tls_ca_cert = Some("<redacted>".to_string());
+        }
+
+        toml::to_string_pretty(&redacted).context("Failed to render config as TOML")
+    }
+}
+
+impl IngestionConfig {
+    pub fn has_source(&self) -> bool {
+        self.remote_store_url.is_some() || self.local_ingestion_path.is_some() || self.rpc_api_url.is_some()
+    }
+
+    /// Fill in the checkpoint source and first checkpoint from `defaults` where
+    /// they are unset. The package versions' first checkpoint, when known, takes
+    /// precedence over the network's. Without either there is no telling where
+    /// the package's history starts, so this fails rather than index from
+    /// genesis; a first checkpoint of 0 asks for that explicitly. Everything
+    /// that can be filled in is, even on error.
+    pub fn apply_defaults(&mut self, defaults: &NetworkDefaults, package_first_checkpoint: u64) -> Result<()> {
+        let mut missing = Vec::new();
+
+        if !self.has_source() {
+            if let Some(path) = defaults.local_ingestion_path {
+                self.local_ingestion_path = Some(PathBuf::from(path));
+            } else if let Some(url) = defaults.remote_store_url {
+                self.remote_store_url = Some(Url::parse(url)?);
+            } else {
+                missing.push(
+                    "No checkpoint source; pass --remote-store-url, --local-ingestion-path or --rpc-api-url",
+                );
+            }
+        }
+
+        if self.first_checkpoint.is_none() {
+            self.first_checkpoint = (package_first_checkpoint > 0)
+                .then_some(package_first_checkpoint)
+                .or(defaults.first_checkpoint);
+
+            if self.first_checkpoint.is_none() {
+                missing.push(
+                    "No first checkpoint is known for the PriceLess package; give the original package \
+                     its publish checkpoint as <id>@<checkpoint>, or pass --first-checkpoint \
+                     (0 to index from genesis)",
+                );
+            }
+        }
+
+        if !missing.is_empty() {
+            bail!(missing.join("; "));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for DefaultsConfig {
+    fn default() -> Self {
+        Self {
+            agent_rating: DEFAULT_AGENT_RATING,
+        }
+    }
+}
+
+impl PipelineLayer {
+    pub fn finish(self, base: SequentialConfig) -> SequentialConfig {
+        SequentialConfig {
+            committer: CommitterConfig {
+                write_concurrency: self
+                    .write_concurrency
+                    .unwrap_or(base.committer.write_concurrency),
+                collect_interval_ms: self
+                    .collect_interval_ms
+                    .unwrap_or(base.committer.collect_interval_ms),
+                watermark_interval_ms: self
+                    .watermark_interval_ms
+                    .unwrap_or(base.committer.watermark_interval_ms),
+            },
+            checkpoint_lag: self.checkpoint_lag.unwrap_or(base.checkpoint_lag),
+        }
+    }
+
+    pub fn finish_concurrent(self, base: ConcurrentConfig) -> ConcurrentConfig {
+        ConcurrentConfig {
+            committer: CommitterConfig {
+                write_concurrency: self
+                    .write_concurrency
+                    .unwrap_or(base.committer.write_concurrency),
+                collect_interval_ms: self
+                    .collect_interval_ms
+                    .unwrap_or(base.committer.collect_interval_ms),
+                watermark_interval_ms: self
+                    .watermark_interval_ms
+                    .unwrap_or(base.committer.watermark_interval_ms),
+            },
+            ..base
+        }
+    }
+
+    /// The layer with every field filled in from `config`.
+    pub fn from_config(config: &SequentialConfig) -> Self {
+        Self {
+            write_concurrency: Some(config.committer.write_concurrency),
+            collect_interval_ms: Some(config.committer.collect_interval_ms),
+            watermark_interval_ms: Some(config.committer.watermark_interval_ms),
+            checkpoint_lag: Some(config.checkpoint_lag),
+        }
+    }
+
+    /// The layer with every committer field filled in from `config`.
+    pub fn from_concurrent_config(config: &ConcurrentConfig) -> Self {
+        Self {
+            write_concurrency: Some(config.committer.write_concurrency),
+            collect_interval_ms: Some(config.committer.collect_interval_ms),
+            watermark_interval_ms: Some(config.committer.watermark_interval_ms),
+            checkpoint_lag: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str = "0x1";
+    const UPGRADE: &str = "0x2";
+
+    fn id(literal: &str) -> ObjectID {
+        ObjectID::from_hex_literal(literal).unwrap()
+    }
+
+    #[test]
+    fn parse_reads_versions_in_order() {
+        let config = PackageConfig::parse(" 0x1 , 0x2@1200 ,").unwrap();
+
+        assert_eq!(
+            config.versions,
+            [
+                PackageVersion { package_id: id(ORIGINAL), first_checkpoint: 0 },
+                PackageVersion { package_id: id(UPGRADE), first_checkpoint: 1200 },
+            ]
+        );
+        assert_eq!(config.first_checkpoint(), 0);
+        assert_eq!(config.versions[1].to_string(), format!("{}@1200", id(UPGRADE)));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_lists() {
+        for (versions, error) in [
+            ("", "At least one package id is required"),
+            (" , ", "At least one package id is required"),
+            ("0x1@soon", "Invalid checkpoint in '0x1@soon'"),
+            ("not-an-id", "'not-an-id' is not a valid Sui object ID"),
+            ("0x1,0x1@5", "is listed more than once"),
+            ("0x1@10,0x2@5", "must be ordered by first checkpoint"),
+        ] {
+            let err = PackageConfig::parse(versions).unwrap_err();
+            assert!(format!("{err:#}").contains(error), "{versions:?}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn first_checkpoint_is_the_earliest_version() {
+        let config = PackageConfig::parse("0x1@100,0x2@200").unwrap();
+        assert_eq!(config.first_checkpoint(), 100);
+    }
+
+    #[test]
+    fn network_defaults_fill_in_the_source_and_first_checkpoint() {
+        let mut ingestion = IngestionConfig::default();
+        ingestion.apply_defaults(&Network::Localnet.defaults(), 0).unwrap();
+
+        assert_eq!(ingestion.local_ingestion_path, Some(PathBuf::from("./checkpoints")));
+        assert_eq!(ingestion.remote_store_url, None);
+        assert_eq!(ingestion.first_checkpoint, Some(0));
+    }
+
+    #[test]
+    fn package_first_checkpoint_wins_over_the_network_hint() {
+        let mut ingestion = IngestionConfig::default();
+        ingestion.apply_defaults(&Network::Localnet.defaults(), 1200).unwrap();
+        assert_eq!(ingestion.first_checkpoint, Some(1200));
+    }
+
+    #[test]
+    fn configured_ingestion_wins_over_network_defaults() {
+        let mut ingestion = IngestionConfig {
+            rpc_api_url: Some(Url::parse("https://rpc.example.com/").unwrap()),
+            first_checkpoint: Some(7),
+            ..IngestionConfig::default()
+        };
+        let configured = ingestion.clone();
+
+        ingestion.apply_defaults(&Network::Testnet.defaults(), 1200).unwrap();
+        assert_eq!(ingestion, configured);
+    }
+
+    #[test]
+    fn unknown_first_checkpoint_is_an_error() {
+        let mut ingestion = IngestionConfig::default();
+        let err = ingestion.apply_defaults(&Network::Testnet.defaults(), 0).unwrap_err();
+        assert!(err.to_string().contains("--first-checkpoint"), "{err}");
+
+        // The source is still filled in, for print-config.
+        assert!(ingestion.remote_store_url.is_some());
+
+        // Unless asked to start from genesis.
+        let mut ingestion = IngestionConfig {
+            first_checkpoint: Some(0),
+            ..IngestionConfig::default()
+        };
+        ingestion.apply_defaults(&Network::Testnet.defaults(), 0).unwrap();
+    }
+
+    #[test]
+    fn version_at_requires_a_known_active_version() {
+        let config = PackageConfig::parse("0x1@100,0x2@200").unwrap();
+
+        assert_eq!(config.version_at(&id(ORIGINAL), 100).unwrap().package_id, id(ORIGINAL));
+        assert_eq!(config.version_at(&id(UPGRADE), 250).unwrap().package_id, id(UPGRADE));
+
+        // Before the version was published, or a package that is not configured.
+        assert!(config.version_at(&id(ORIGINAL), 99).is_none());
+        assert!(config.version_at(&id(UPGRADE), 150).is_none());
+        assert!(config.version_at(&id("0x3"), 250).is_none());
+    }
+}
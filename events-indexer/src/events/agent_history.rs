@@ -0,0 +1,110 @@
+use anyhow::Error;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::{Connection, EventOrigin};
+use crate::schema::AgentHistory;
+
+/// Kind of change recorded in `AgentHistory.change`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentChange {
+    Registered,
+    Staked,
+    Unstaked,
+    Slashed,
+    Deactivated,
+    Reactivated,
+    RatingUpdated,
+}
+
+impl AgentChange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AgentChange::Registered => "registered",
+            AgentChange::Staked => "staked",
+            AgentChange::Unstaked => "unstaked",
+            AgentChange::Slashed => "slashed",
+            AgentChange::Deactivated => "deactivated",
+            AgentChange::Reactivated => "reactivated",
+            AgentChange::RatingUpdated => "rating_updated",
+        }
+    }
+}
+
+/// One change to an agent. `stake_amount`, `active` and `rating` hold the
+/// agent's values after the change, and are `None` when the event does not
+/// affect them.
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = AgentHistory)]
+pub struct AgentHistoryValue {
+    pub agent_id: String,
+    pub change: String,
+    pub stake_delta: Option<BigDecimal>,
+    pub stake_amount: Option<BigDecimal>,
+    pub active: Option<bool>,
+    pub rating: Option<BigDecimal>,
+    pub changed_at: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+impl AgentHistoryValue {
+    /// A change with no effect on stake, activity or rating yet; callers fill
+    /// in the fields their event sets.
+    pub fn new(agent_id: String, change: AgentChange, changed_at: BigDecimal, origin: &EventOrigin) -> Self {
+        Self {
+            agent_id,
+            change: change.as_str().to_string(),
+            stake_delta: None,
+            stake_amount: None,
+            active: None,
+            rating: None,
+            changed_at,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        }
+    }
+}
+
+/// Recompute `stake_amount`, `active` and `rating` for one agent from the
+/// latest history row setting each of them.
+///
+/// Deriving the state from history rather than applying each event to the
+/// current row means replaying an old checkpoint range cannot roll an agent
+/// back to a stale value. Rows are ordered by checkpoint, then by insertion,
+/// which follows chain order within a checkpoint.
+pub(crate) async fn refresh_agent_state<'a>(agent_id: &str, conn: &mut Connection<'a>) -> Result<usize> {
+    diesel::sql_query(
+        r#"
+        UPDATE "Agent" SET
+            stake_amount = COALESCE((
+                SELECT h.stake_amount FROM "AgentHistory" h
+                WHERE h.agent_id = $1 AND h.stake_amount IS NOT NULL
+                ORDER BY h.checkpoint DESC, h.id DESC LIMIT 1
+            ), "Agent".stake_amount),
+            active = COALESCE((
+                SELECT h.active FROM "AgentHistory" h
+                WHERE h.agent_id = $1 AND h.active IS NOT NULL
+                ORDER BY h.checkpoint DESC, h.id DESC LIMIT 1
+            ), "Agent".active),
+            rating = COALESCE((
+                SELECT h.rating FROM "AgentHistory" h
+                WHERE h.agent_id = $1 AND h.rating IS NOT NULL
+                ORDER BY h.checkpoint DESC, h.id DESC LIMIT 1
+            ), "Agent".rating)
+        WHERE "Agent".agent_id = $1
+        "#,
+    )
+    .bind::<Text, _>(agent_id)
+    .execute(conn)
+    .await
+    .map_err(Into::<Error>::into)
+}
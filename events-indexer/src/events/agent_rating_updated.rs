@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentRatingUpdatedEvent {
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub old_rating: u64,
+    pub new_rating: u64,
+    pub timestamp: u64,
+}
+
+/// Not registered by default: ratings are only set at registration today.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct AgentRatingUpdatedHandler;
+
+#[async_trait]
+impl EventHandler for AgentRatingUpdatedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentRatingUpdated";
+    const TABLES: &'static [&'static str] = &["AgentHistory", "Agent"];
+
+    type Event = AgentRatingUpdatedEvent;
+    type Value = AgentHistoryValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentHistoryValue> {
+        let agent_rating_updated_event: AgentRatingUpdatedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentRatingUpdated - agent_id: {}, agent_address: {}, old_rating: {}, new_rating: {}, timestamp: {}, tx: {}",
+            agent_rating_updated_event.agent_id, agent_rating_updated_event.agent_address, agent_rating_updated_event.old_rating, agent_rating_updated_event.new_rating, agent_rating_updated_event.timestamp, origin.tx_digest
+        );
+
+        let rating = BigDecimal::from(agent_rating_updated_event.new_rating);
+        let changed_at = BigDecimal::from(agent_rating_updated_event.timestamp);
+
+        Ok(AgentHistoryValue {
+            rating: Some(rating),
+            ..AgentHistoryValue::new(
+                agent_rating_updated_event.agent_id.to_string(),
+                AgentChange::RatingUpdated,
+                changed_at,
+                origin,
+            )
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentHistoryValue, batch: &mut InsertBatch<'v>) {
+        batch.agent_history.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &AgentHistoryValue, conn: &mut Connection<'a>) -> Result<usize> {
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
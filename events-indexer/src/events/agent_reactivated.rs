@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentReactivatedEvent {
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub timestamp: u64,
+}
+
+/// Not registered by default, as the package has no `AgentReactivated` event
+/// yet.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct AgentReactivatedHandler;
+
+#[async_trait]
+impl EventHandler for AgentReactivatedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentReactivated";
+    const TABLES: &'static [&'static str] = &["AgentHistory", "Agent"];
+
+    type Event = AgentReactivatedEvent;
+    type Value = AgentHistoryValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentHistoryValue> {
+        let agent_reactivated_event: AgentReactivatedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentReactivated - agent_id: {}, agent_address: {}, timestamp: {}, tx: {}",
+            agent_reactivated_event.agent_id, agent_reactivated_event.agent_address, agent_reactivated_event.timestamp, origin.tx_digest
+        );
+
+        let changed_at = BigDecimal::from(agent_reactivated_event.timestamp);
+
+        Ok(AgentHistoryValue {
+            active: Some(true),
+            ..AgentHistoryValue::new(
+                agent_reactivated_event.agent_id.to_string(),
+                AgentChange::Reactivated,
+                changed_at,
+                origin,
+            )
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentHistoryValue, batch: &mut InsertBatch<'v>) {
+        batch.agent_history.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &AgentHistoryValue, conn: &mut Connection<'a>) -> Result<usize> {
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
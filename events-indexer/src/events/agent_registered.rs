@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::config::DEFAULT_AGENT_RATING;
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::Agent;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentRegisteredEvent {
+    pub agent_id: ObjectID,
+    pub agent_object_address: SuiAddress,
+    pub agent_owner_address: SuiAddress,
+    pub stake_amount: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = Agent)]
+pub struct AgentValue {
+    pub agent_id: String,
+    pub agent_address: String,
+    pub agent_owner_address: String,
+    pub stake_amount: BigDecimal,
+    pub rating: BigDecimal,
+    pub active: bool,
+    pub registered_at: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+impl AgentValue {
+    /// The `AgentHistory` row recording this registration.
+    pub fn history(&self) -> AgentHistoryValue {
+        let origin = EventOrigin {
+            tx_digest: self.tx_digest.clone(),
+            event_seq: self.event_seq,
+            checkpoint: self.checkpoint,
+            checkpoint_timestamp_ms: self.checkpoint_timestamp_ms,
+        };
+
+        AgentHistoryValue {
+            stake_delta: Some(self.stake_amount.clone()),
+            stake_amount: Some(self.stake_amount.clone()),
+            active: Some(self.active),
+            rating: Some(self.rating.clone()),
+            ..AgentHistoryValue::new(
+                self.agent_id.clone(),
+                AgentChange::Registered,
+                self.registered_at.clone(),
+                &origin,
+            )
+        }
+    }
+}
+
+pub struct AgentRegisteredHandler {
+    /// Rating recorded for a newly registered agent.
+    pub initial_rating: u64,
+}
+
+impl Default for AgentRegisteredHandler {
+    fn default() -> Self {
+        Self {
+            initial_rating: DEFAULT_AGENT_RATING,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for AgentRegisteredHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentRegistered";
+    const TABLES: &'static [&'static str] = &["Agent", "AgentHistory"];
+
+    type Event = AgentRegisteredEvent;
+    type Value = AgentValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentValue> {
+        let agent_event: AgentRegisteredEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentRegistered - agent_id: {}, object_address: {}, owner_address: {}, stake_amount: {}, timestamp: {}, tx: {}",
+            agent_event.agent_id, agent_event.agent_object_address, agent_event.agent_owner_address, agent_event.stake_amount, agent_event.timestamp, origin.tx_digest
+        );
+
+        let stake_amount = BigDecimal::from(agent_event.stake_amount);
+        let registered_at = BigDecimal::from(agent_event.timestamp);
+
+        Ok(AgentValue {
+            agent_id: agent_event.agent_id.to_string(),
+            agent_address: agent_event.agent_object_address.to_string(),
+            agent_owner_address: agent_event.agent_owner_address.to_string(),
+            stake_amount,
+            rating: BigDecimal::from(self.initial_rating),
+            active: true,
+            registered_at,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentValue, batch: &mut InsertBatch<'v>) {
+        batch.agents.push(Cow::Borrowed(value));
+        batch.agent_history.push(Cow::Owned(value.history()));
+    }
+
+    async fn commit<'a>(value: &AgentValue, conn: &mut Connection<'a>) -> Result<usize> {
+        // History may already hold later changes, e.g. when replaying a range.
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+
+/// Emitted when part of an agent's stake is confiscated. `stake_amount` is the
+/// stake left afterwards.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentSlashedEvent {
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub slashed_amount: u64,
+    pub stake_amount: u64,
+    pub timestamp: u64,
+}
+
+/// Not registered by default, as the package has no `AgentSlashed` event yet.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct AgentSlashedHandler;
+
+#[async_trait]
+impl EventHandler for AgentSlashedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentSlashed";
+    const TABLES: &'static [&'static str] = &["AgentHistory", "Agent"];
+
+    type Event = AgentSlashedEvent;
+    type Value = AgentHistoryValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentHistoryValue> {
+        let agent_slashed_event: AgentSlashedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentSlashed - agent_id: {}, agent_address: {}, slashed_amount: {}, stake_amount: {}, timestamp: {}, tx: {}",
+            agent_slashed_event.agent_id, agent_slashed_event.agent_address, agent_slashed_event.slashed_amount, agent_slashed_event.stake_amount, agent_slashed_event.timestamp, origin.tx_digest
+        );
+
+        let slashed_amount = BigDecimal::from(agent_slashed_event.slashed_amount);
+        let stake_amount = BigDecimal::from(agent_slashed_event.stake_amount);
+        let changed_at = BigDecimal::from(agent_slashed_event.timestamp);
+
+        Ok(AgentHistoryValue {
+            stake_delta: Some(-slashed_amount),
+            stake_amount: Some(stake_amount),
+            ..AgentHistoryValue::new(
+                agent_slashed_event.agent_id.to_string(),
+                AgentChange::Slashed,
+                changed_at,
+                origin,
+            )
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentHistoryValue, batch: &mut InsertBatch<'v>) {
+        batch.agent_history.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &AgentHistoryValue, conn: &mut Connection<'a>) -> Result<usize> {
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
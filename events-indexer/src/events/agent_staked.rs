@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+
+/// Emitted when an agent tops up its stake. `stake_amount` is the stake after
+/// the top-up.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentStakedEvent {
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub staked_amount: u64,
+    pub stake_amount: u64,
+    pub timestamp: u64,
+}
+
+/// Not registered by default; the layout follows the other agent events until
+/// the package emits `AgentStaked`.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct AgentStakedHandler;
+
+#[async_trait]
+impl EventHandler for AgentStakedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentStaked";
+    const TABLES: &'static [&'static str] = &["AgentHistory", "Agent"];
+
+    type Event = AgentStakedEvent;
+    type Value = AgentHistoryValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentHistoryValue> {
+        let agent_staked_event: AgentStakedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentStaked - agent_id: {}, agent_address: {}, staked_amount: {}, stake_amount: {}, timestamp: {}, tx: {}",
+            agent_staked_event.agent_id, agent_staked_event.agent_address, agent_staked_event.staked_amount, agent_staked_event.stake_amount, agent_staked_event.timestamp, origin.tx_digest
+        );
+
+        let staked_amount = BigDecimal::from(agent_staked_event.staked_amount);
+        let stake_amount = BigDecimal::from(agent_staked_event.stake_amount);
+        let changed_at = BigDecimal::from(agent_staked_event.timestamp);
+
+        Ok(AgentHistoryValue {
+            stake_delta: Some(staked_amount),
+            stake_amount: Some(stake_amount),
+            ..AgentHistoryValue::new(
+                agent_staked_event.agent_id.to_string(),
+                AgentChange::Staked,
+                changed_at,
+                origin,
+            )
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentHistoryValue, batch: &mut InsertBatch<'v>) {
+        batch.agent_history.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &AgentHistoryValue, conn: &mut Connection<'a>) -> Result<usize> {
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
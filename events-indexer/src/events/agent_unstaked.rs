@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_history::{refresh_agent_state, AgentChange, AgentHistoryValue};
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AgentUnstakedEvent {
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub unstaked_amount: u64,
+    pub timestamp: u64,
+}
+
+/// Unstaking withdraws the whole stake and deactivates the agent.
+pub struct AgentUnstakedHandler;
+
+#[async_trait]
+impl EventHandler for AgentUnstakedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AgentUnstaked";
+    const TABLES: &'static [&'static str] = &["AgentHistory", "Agent"];
+
+    type Event = AgentUnstakedEvent;
+    type Value = AgentHistoryValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AgentHistoryValue> {
+        let agent_unstaked_event: AgentUnstakedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AgentUnstaked - agent_id: {}, agent_address: {}, unstaked_amount: {}, timestamp: {}, tx: {}",
+            agent_unstaked_event.agent_id, agent_unstaked_event.agent_address, agent_unstaked_event.unstaked_amount, agent_unstaked_event.timestamp, origin.tx_digest
+        );
+
+        let unstaked_amount = BigDecimal::from(agent_unstaked_event.unstaked_amount);
+        let changed_at = BigDecimal::from(agent_unstaked_event.timestamp);
+
+        Ok(AgentHistoryValue {
+            stake_delta: Some(-unstaked_amount),
+            stake_amount: Some(BigDecimal::from(0)),
+            active: Some(false),
+            ..AgentHistoryValue::new(
+                agent_unstaked_event.agent_id.to_string(),
+                AgentChange::Unstaked,
+                changed_at,
+                origin,
+            )
+        })
+    }
+
+    fn stage<'v>(value: &'v AgentHistoryValue, batch: &mut InsertBatch<'v>) {
+        batch.agent_history.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &AgentHistoryValue, conn: &mut Connection<'a>) -> Result<usize> {
+        refresh_agent_state(&value.agent_id, conn).await
+    }
+}
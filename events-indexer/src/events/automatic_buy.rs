@@ -0,0 +1,91 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::manual_buy::fulfil_buy_offer;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::SellOffer;
+
+/// Emitted for every purchase made on the buyer's behalf, before the offer is
+/// deleted and the shop purchase is recorded.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AutomaticBuyEvent {
+    pub buy_offer_id: ObjectID,
+    pub buyer: SuiAddress,
+    pub agent_id: ObjectID,
+    pub store_link: String,
+    pub product_price: u64,
+    pub agent_fee: u64,
+    pub platform_fee: u64,
+    pub buyer_savings: u64,
+}
+
+#[derive(Debug)]
+pub struct AutomaticBuyData {
+    pub buy_offer_id: String,
+    pub agent_id: String,
+    /// Product price plus the agent's and the platform's fees.
+    pub total_paid: BigDecimal,
+    pub checkpoint: i64,
+}
+
+/// Moves the buy offer to `fulfilled`, like `ManualBuy`. The event does not
+/// name the sell offer, but an agent makes at most one per buy offer, so the
+/// winner is the agent's.
+pub struct AutomaticBuyHandler;
+
+#[async_trait]
+impl EventHandler for AutomaticBuyHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "AutomaticBuy";
+    const TABLES: &'static [&'static str] = &["BuyOffer", "SellOffer"];
+
+    type Event = AutomaticBuyEvent;
+    type Value = AutomaticBuyData;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<AutomaticBuyData> {
+        let automatic_buy_event: AutomaticBuyEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed AutomaticBuy - buy_offer_id: {}, buyer: {}, agent_id: {}, product_price: {}, tx: {}",
+            automatic_buy_event.buy_offer_id, automatic_buy_event.buyer, automatic_buy_event.agent_id, automatic_buy_event.product_price, origin.tx_digest
+        );
+
+        let total_paid = BigDecimal::from(automatic_buy_event.product_price)
+            + BigDecimal::from(automatic_buy_event.agent_fee)
+            + BigDecimal::from(automatic_buy_event.platform_fee);
+
+        Ok(AutomaticBuyData {
+            buy_offer_id: automatic_buy_event.buy_offer_id.to_string(),
+            agent_id: automatic_buy_event.agent_id.to_string(),
+            total_paid,
+            checkpoint: origin.checkpoint,
+        })
+    }
+
+    async fn commit<'a>(value: &AutomaticBuyData, conn: &mut Connection<'a>) -> Result<usize> {
+        let sell_offer_id: Option<String> = SellOffer::table
+            .filter(SellOffer::buy_offer_id.eq(&value.buy_offer_id))
+            .filter(SellOffer::agent_id.eq(&value.agent_id))
+            .select(SellOffer::sell_offer_id)
+            .first(conn)
+            .await
+            .optional()
+            .map_err(Into::<Error>::into)?;
+
+        fulfil_buy_offer(
+            &value.buy_offer_id,
+            sell_offer_id.as_deref(),
+            &value.total_paid,
+            value.checkpoint,
+            conn,
+        )
+        .await
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::Error;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::warn;
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::agent_history::AgentHistoryValue;
+use crate::events::agent_registered::AgentValue;
+use crate::events::buy_offer_created::BuyOfferValue;
+use crate::events::buy_offer_modified::BuyOfferPriceChangeValue;
+use crate::events::failed_event::FailedEventValue;
+use crate::events::manual_buy::ManualBuyValue;
+use crate::events::raw_event::RawEventValue;
+use crate::events::sell_offer_made::{indexed_buy_offers, SellOfferRevisionValue, SellOfferValue};
+use crate::events::shop_purchase::ShopPurchaseValue;
+use crate::events::user_registered::UserValue;
+use crate::events::Connection;
+use crate::schema::{
+    Agent, AgentHistory, BuyOffer, BuyOfferPriceChange, FailedEvent, ManualBuy, RawEvent, SellOffer,
+    SellOfferRevision, ShopPurchase, User,
+};
+
+/// Postgres accepts at most `u16::MAX` bind parameters per statement. Stay
+/// well below it, as the framework's own pipelines do.
+const MAX_BIND_PARAMS: usize = i16::MAX as usize;
+
+/// Rows of a batch that are only ever inserted, grouped by table so each table
+/// is written with a few multi-row statements instead of one per event.
+///
+/// Every insert ignores conflicts, so the rows can be written before the rest
+/// of the batch is applied in chain order. Rows with an on-chain id conflict on
+/// it, so a replayed event and a second event for the same object are both
+/// ignored; the others conflict on the event that wrote them.
+#[derive(Debug, Default)]
+pub struct InsertBatch<'v> {
+    pub raw_events: Vec<Cow<'v, RawEventValue>>,
+    pub failed_events: Vec<Cow<'v, FailedEventValue>>,
+    pub users: Vec<Cow<'v, UserValue>>,
+    pub agents: Vec<Cow<'v, AgentValue>>,
+    pub agent_history: Vec<Cow<'v, AgentHistoryValue>>,
+    pub buy_offers: Vec<Cow<'v, BuyOfferValue>>,
+    pub buy_offer_price_changes: Vec<Cow<'v, BuyOfferPriceChangeValue>>,
+    /// New sell offers only. Revisions update the current row, so they are
+    /// applied in order by `SellOfferMadeHandler::commit`.
+    pub sell_offers: Vec<Cow<'v, SellOfferValue>>,
+    pub sell_offer_revisions: Vec<Cow<'v, SellOfferRevisionValue>>,
+    pub manual_buys: Vec<Cow<'v, ManualBuyValue>>,
+    pub shop_purchases: Vec<Cow<'v, ShopPurchaseValue>>,
+}
+
+/// Insert `$rows` into `$table` in chunks, ignoring rows that conflict on
+/// `$conflict`, and evaluate to the number of rows inserted.
+macro_rules! insert_chunked {
+    ($conn:expr, $table:ident, $rows:expr, $conflict:expr) => {{
+        let mut count = 0;
+
+        for chunk in $rows.chunks(chunk_rows($rows)) {
+            let values: Vec<_> = chunk.iter().map(|row| row.as_ref()).collect();
+
+            count += diesel::insert_into($table::table)
+                .values(values)
+                .on_conflict($conflict)
+                .do_nothing()
+                .execute($conn)
+                .await
+                .map_err(Into::<Error>::into)?;
+        }
+
+        count
+    }};
+}
+
+impl InsertBatch<'_> {
+    /// Drop the rows of every table not listed in `tables`, which another
+    /// pipeline writes.
+    pub fn retain_tables(&mut self, tables: &[&str]) {
+        let keeps = |table: &str| tables.contains(&table);
+
+        if !keeps("RawEvent") {
+            self.raw_events.clear();
+        }
+        if !keeps("FailedEvent") {
+            self.failed_events.clear();
+        }
+        if !keeps("User") {
+            self.users.clear();
+        }
+        if !keeps("Agent") {
+            self.agents.clear();
+        }
+        if !keeps("AgentHistory") {
+            self.agent_history.clear();
+        }
+        if !keeps("BuyOffer") {
+            self.buy_offers.clear();
+        }
+        if !keeps("BuyOfferPriceChange") {
+            self.buy_offer_price_changes.clear();
+        }
+        if !keeps("SellOffer") {
+            self.sell_offers.clear();
+        }
+        if !keeps("SellOfferRevision") {
+            self.sell_offer_revisions.clear();
+        }
+        if !keeps("ManualBuy") {
+            self.manual_buys.clear();
+        }
+        if !keeps("ShopPurchase") {
+            self.shop_purchases.clear();
+        }
+    }
+
+    /// Insert every row, referenced tables first, returning the number of rows
+    /// inserted.
+    pub async fn write<'a>(&self, conn: &mut Connection<'a>) -> Result<usize> {
+        let mut count = 0;
+
+        count += insert_chunked!(conn, RawEvent, &self.raw_events, (RawEvent::tx_digest, RawEvent::event_seq));
+        count += insert_chunked!(
+            conn,
+            FailedEvent,
+            &self.failed_events,
+            (FailedEvent::tx_digest, FailedEvent::event_seq)
+        );
+        count += insert_chunked!(conn, User, &self.users, User::user_owner_address);
+        count += insert_chunked!(conn, Agent, &self.agents, Agent::agent_id);
+        count += insert_chunked!(
+            conn,
+            AgentHistory,
+            &self.agent_history,
+            (AgentHistory::tx_digest, AgentHistory::event_seq)
+        );
+        count += insert_chunked!(conn, BuyOffer, &self.buy_offers, BuyOffer::buy_offer_id);
+        count += insert_chunked!(
+            conn,
+            BuyOfferPriceChange,
+            &self.buy_offer_price_changes,
+            (BuyOfferPriceChange::tx_digest, BuyOfferPriceChange::event_seq)
+        );
+        let sell_offers = self.sell_offers_with_buy_offer(conn).await?;
+        count += insert_chunked!(conn, SellOffer, &sell_offers, SellOffer::sell_offer_id);
+        count += insert_chunked!(
+            conn,
+            SellOfferRevision,
+            &self.sell_offer_revisions,
+            (SellOfferRevision::tx_digest, SellOfferRevision::event_seq)
+        );
+        count += insert_chunked!(conn, ManualBuy, &self.manual_buys, (ManualBuy::tx_digest, ManualBuy::event_seq));
+        count += insert_chunked!(
+            conn,
+            ShopPurchase,
+            &self.shop_purchases,
+            (ShopPurchase::tx_digest, ShopPurchase::event_seq)
+        );
+
+        Ok(count)
+    }
+
+    /// The new sell offers whose buy offer is indexed, once this batch's buy
+    /// offers are written. See [`indexed_buy_offers`].
+    async fn sell_offers_with_buy_offer<'a>(
+        &self,
+        conn: &mut Connection<'a>,
+    ) -> Result<Vec<Cow<'_, SellOfferValue>>> {
+        if self.sell_offers.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let ids: Vec<&str> = self.sell_offers.iter().map(|s| s.buy_offer_id.as_str()).collect();
+        let indexed = indexed_buy_offers(&ids, conn).await?;
+
+        let (kept, skipped): (Vec<_>, Vec<_>) = self
+            .sell_offers
+            .iter()
+            .map(|s| Cow::Borrowed(s.as_ref()))
+            .partition(|s| indexed.contains(&s.buy_offer_id));
+
+        for s in &skipped {
+            warn!(
+                "Skipping SellOffer {} made in {}: its BuyOffer {} was never indexed",
+                s.sell_offer_id, s.tx_digest, s.buy_offer_id
+            );
+        }
+
+        Ok(kept)
+    }
+}
+
+/// Rows per statement for values of type `T`.
+fn chunk_rows<T: Clone + FieldCount>(_rows: &[Cow<'_, T>]) -> usize {
+    MAX_BIND_PARAMS / T::FIELD_COUNT
+}
@@ -0,0 +1,75 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::BuyOffer;
+use crate::status::BuyOfferStatus;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BuyOfferCancelledEvent {
+    pub buy_offer_id: ObjectID,
+    pub owner: SuiAddress,
+    pub refunded_amount: u64,
+}
+
+#[derive(Debug)]
+pub struct BuyOfferCancelledData {
+    pub buy_offer_id: String,
+    pub refunded_amount: BigDecimal,
+    pub checkpoint: i64,
+}
+
+pub struct BuyOfferCancelledHandler;
+
+#[async_trait]
+impl EventHandler for BuyOfferCancelledHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "BuyOfferCancelled";
+    const TABLES: &'static [&'static str] = &["BuyOffer"];
+
+    type Event = BuyOfferCancelledEvent;
+    type Value = BuyOfferCancelledData;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<BuyOfferCancelledData> {
+        let buy_offer_cancelled_event: BuyOfferCancelledEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed BuyOfferCancelled - buy_offer_id: {}, owner: {}, refunded_amount: {}, tx: {}",
+            buy_offer_cancelled_event.buy_offer_id, buy_offer_cancelled_event.owner, buy_offer_cancelled_event.refunded_amount, origin.tx_digest
+        );
+
+        let refunded_amount = BigDecimal::from(buy_offer_cancelled_event.refunded_amount);
+
+        Ok(BuyOfferCancelledData {
+            buy_offer_id: buy_offer_cancelled_event.buy_offer_id.to_string(),
+            refunded_amount,
+            checkpoint: origin.checkpoint,
+        })
+    }
+
+    async fn commit<'a>(value: &BuyOfferCancelledData, conn: &mut Connection<'a>) -> Result<usize> {
+        diesel::update(
+            BuyOffer::table
+                .filter(BuyOffer::buy_offer_id.eq(&value.buy_offer_id))
+                .filter(BuyOffer::status.eq_any([
+                    BuyOfferStatus::Open.as_str(),
+                    BuyOfferStatus::Expired.as_str(),
+                ])),
+        )
+        .set((
+            BuyOffer::status.eq(BuyOfferStatus::Cancelled.as_str()),
+            BuyOffer::remaining_balance.eq(&value.refunded_amount),
+            BuyOffer::closed_at.eq(value.checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::batch::InsertBatch;
+use crate::events::{EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::BuyOffer;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BuyOfferCreatedEvent {
+    pub buy_offer_id: ObjectID,
+    pub owner: SuiAddress,
+    pub product: String,
+    pub price: u64,
+    pub offer_type_is_time_based: bool,
+    pub deadline: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = BuyOffer)]
+pub struct BuyOfferValue {
+    pub buy_offer_id: String,
+    pub owner: String,
+    pub product: String,
+    pub price: BigDecimal,
+    pub offer_type_is_time_based: bool,
+    pub deadline: BigDecimal,
+    pub created_at: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+pub struct BuyOfferCreatedHandler;
+
+#[async_trait]
+impl EventHandler for BuyOfferCreatedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "BuyOfferCreated";
+    const TABLES: &'static [&'static str] = &["BuyOffer"];
+
+    type Event = BuyOfferCreatedEvent;
+    type Value = BuyOfferValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<BuyOfferValue> {
+        let buy_offer_event: BuyOfferCreatedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed BuyOfferCreated - buy_offer_id: {}, owner: {}, product: {}, price: {}, is_time_based: {}, deadline: {}, timestamp: {}, tx: {}",
+            buy_offer_event.buy_offer_id, buy_offer_event.owner, buy_offer_event.product, buy_offer_event.price, buy_offer_event.offer_type_is_time_based, buy_offer_event.deadline, buy_offer_event.timestamp, origin.tx_digest
+        );
+
+        let price = BigDecimal::from(buy_offer_event.price);
+        let deadline = BigDecimal::from(buy_offer_event.deadline);
+        let created_at = BigDecimal::from(buy_offer_event.timestamp);
+
+        Ok(BuyOfferValue {
+            buy_offer_id: buy_offer_event.buy_offer_id.to_string(),
+            owner: buy_offer_event.owner.to_string(),
+            product: buy_offer_event.product,
+            price,
+            offer_type_is_time_based: buy_offer_event.offer_type_is_time_based,
+            deadline,
+            created_at,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v BuyOfferValue, batch: &mut InsertBatch<'v>) {
+        batch.buy_offers.push(Cow::Borrowed(value));
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::{BuyOffer, SellOffer};
+use crate::status::{BuyOfferStatus, SellOfferStatus};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BuyOfferDeletedEvent {
+    pub buy_offer_id: ObjectID,
+    pub owner: SuiAddress,
+    pub remaining_balance: u64,
+}
+
+#[derive(Debug)]
+pub struct BuyOfferDeletedData {
+    pub buy_offer_id: String,
+    pub remaining_balance: BigDecimal,
+    pub checkpoint: i64,
+}
+
+impl BuyOfferDeletedData {
+    /// Status of an offer still open or expired when it is deleted. Every buy
+    /// spends the whole balance, while a cancellation refunds it, so the
+    /// remaining balance tells them apart even when `BuyOfferCancelled` is not
+    /// indexed.
+    pub fn closing_status(&self) -> BuyOfferStatus {
+        if self.remaining_balance > BigDecimal::from(0) {
+            BuyOfferStatus::Cancelled
+        } else {
+            BuyOfferStatus::Fulfilled
+        }
+    }
+}
+
+/// Closes a buy offer and its sell offers. The rows are kept for auditing.
+///
+/// A buy offer is only deleted after it was cancelled or bought. Cancellation and
+/// buys are recorded by `BuyOfferCancelled`, `ManualBuy` and `AutomaticBuy`
+/// first; an offer still open here, because those events are not indexed, is
+/// closed with [`BuyOfferDeletedData::closing_status`]. An expired time-based
+/// offer is deleted by the service buy that follows its deadline.
+pub struct BuyOfferDeletedHandler;
+
+#[async_trait]
+impl EventHandler for BuyOfferDeletedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "BuyOfferDeleted";
+    const TABLES: &'static [&'static str] = &["SellOffer", "BuyOffer"];
+
+    type Event = BuyOfferDeletedEvent;
+    type Value = BuyOfferDeletedData;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<BuyOfferDeletedData> {
+        let buy_offer_deleted_event: BuyOfferDeletedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed BuyOfferDeleted - buy_offer_id: {}, owner: {}, remaining_balance: {}, tx: {}",
+            buy_offer_deleted_event.buy_offer_id, buy_offer_deleted_event.owner, buy_offer_deleted_event.remaining_balance, origin.tx_digest
+        );
+
+        let remaining_balance = BigDecimal::from(buy_offer_deleted_event.remaining_balance);
+
+        Ok(BuyOfferDeletedData {
+            buy_offer_id: buy_offer_deleted_event.buy_offer_id.to_string(),
+            remaining_balance,
+            checkpoint: origin.checkpoint,
+        })
+    }
+
+    async fn commit<'a>(value: &BuyOfferDeletedData, conn: &mut Connection<'a>) -> Result<usize> {
+        // Close the sell offers that were still competing for this buy offer.
+        // Sell offers made later in the batch are already inserted; skip them.
+        diesel::update(
+            SellOffer::table
+                .filter(SellOffer::buy_offer_id.eq(&value.buy_offer_id))
+                .filter(SellOffer::status.eq(SellOfferStatus::Open.as_str()))
+                .filter(SellOffer::checkpoint.is_null().or(SellOffer::checkpoint.le(value.checkpoint))),
+        )
+        .set((
+            SellOffer::status.eq(SellOfferStatus::Closed.as_str()),
+            SellOffer::closed_at.eq(value.checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+        diesel::update(
+            BuyOffer::table
+                .filter(BuyOffer::buy_offer_id.eq(&value.buy_offer_id))
+                .filter(BuyOffer::status.eq_any([
+                    BuyOfferStatus::Open.as_str(),
+                    BuyOfferStatus::Expired.as_str(),
+                ])),
+        )
+        .set(BuyOffer::status.eq(value.closing_status().as_str()))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+        diesel::update(BuyOffer::table.filter(BuyOffer::buy_offer_id.eq(&value.buy_offer_id)))
+            .set((
+                BuyOffer::remaining_balance.eq(&value.remaining_balance),
+                BuyOffer::closed_at.eq(value.checkpoint),
+            ))
+            .execute(conn)
+            .await
+            .map_err(Into::<Error>::into)
+    }
+}
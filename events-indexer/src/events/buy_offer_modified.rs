@@ -0,0 +1,97 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::{BuyOffer, BuyOfferPriceChange};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BuyOfferModifiedEvent {
+    pub buy_offer_id: ObjectID,
+    pub owner: SuiAddress,
+    pub old_price: u64,
+    pub new_price: u64,
+    pub price_reduction: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = BuyOfferPriceChange)]
+pub struct BuyOfferPriceChangeValue {
+    pub buy_offer_id: String,
+    pub owner: String,
+    pub old_price: BigDecimal,
+    pub new_price: BigDecimal,
+    pub price_reduction: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+/// Records every price change in `BuyOfferPriceChange` and keeps
+/// `BuyOffer.price` at the latest price.
+pub struct BuyOfferModifiedHandler;
+
+#[async_trait]
+impl EventHandler for BuyOfferModifiedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "BuyOfferModified";
+    const TABLES: &'static [&'static str] = &["BuyOfferPriceChange", "BuyOffer"];
+
+    type Event = BuyOfferModifiedEvent;
+    type Value = BuyOfferPriceChangeValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<BuyOfferPriceChangeValue> {
+        let buy_offer_modified_event: BuyOfferModifiedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed BuyOfferModified - buy_offer_id: {}, owner: {}, old_price: {}, new_price: {}, price_reduction: {}, tx: {}",
+            buy_offer_modified_event.buy_offer_id, buy_offer_modified_event.owner, buy_offer_modified_event.old_price, buy_offer_modified_event.new_price, buy_offer_modified_event.price_reduction, origin.tx_digest
+        );
+
+        let old_price = BigDecimal::from(buy_offer_modified_event.old_price);
+        let new_price = BigDecimal::from(buy_offer_modified_event.new_price);
+        let price_reduction = BigDecimal::from(buy_offer_modified_event.price_reduction);
+
+        Ok(BuyOfferPriceChangeValue {
+            buy_offer_id: buy_offer_modified_event.buy_offer_id.to_string(),
+            owner: buy_offer_modified_event.owner.to_string(),
+            old_price,
+            new_price,
+            price_reduction,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v BuyOfferPriceChangeValue, batch: &mut InsertBatch<'v>) {
+        batch.buy_offer_price_changes.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &BuyOfferPriceChangeValue, conn: &mut Connection<'a>) -> Result<usize> {
+        // Keep the latest price, so replaying an older change does not roll it back.
+        diesel::update(
+            BuyOffer::table
+                .filter(BuyOffer::buy_offer_id.eq(&value.buy_offer_id))
+                .filter(BuyOffer::updated_at.is_null().or(BuyOffer::updated_at.le(value.checkpoint))),
+        )
+        .set((
+            BuyOffer::price.eq(&value.new_price),
+            BuyOffer::updated_at.eq(value.checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
@@ -0,0 +1,40 @@
+use diesel::prelude::*;
+use sui_indexer_alt_framework::FieldCount;
+
+use crate::events::raw_event::RawEventValue;
+use crate::schema::FailedEvent;
+
+/// An event that could not be decoded, kept for `retry-failed`.
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = FailedEvent)]
+pub struct FailedEventValue {
+    pub package_id: String,
+    pub module: String,
+    pub struct_name: String,
+    pub event_type: String,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub sender: String,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+    pub bcs: Vec<u8>,
+    pub error: String,
+}
+
+impl FailedEventValue {
+    pub fn new(raw: &RawEventValue, error: String) -> Self {
+        Self {
+            package_id: raw.package_id.clone(),
+            module: raw.module.clone(),
+            struct_name: raw.struct_name.clone(),
+            event_type: raw.event_type.clone(),
+            tx_digest: raw.tx_digest.clone(),
+            event_seq: raw.event_seq,
+            sender: raw.sender.clone(),
+            checkpoint: raw.checkpoint,
+            checkpoint_timestamp_ms: raw.checkpoint_timestamp_ms,
+            bcs: raw.bcs.clone(),
+            error,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::{BuyOffer, ManualBuy, SellOffer};
+use crate::status::{BuyOfferStatus, SellOfferStatus};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManualBuyEvent {
+    pub buy_offer_id: ObjectID,
+    pub buyer: SuiAddress,
+    pub agent_id: ObjectID,
+    pub sell_offer_id: ObjectID,
+    pub store_link: String,
+    pub product_price: u64,
+    pub agent_fee: u64,
+    pub total_paid: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = ManualBuy)]
+pub struct ManualBuyValue {
+    pub buy_offer_id: String,
+    pub buyer: String,
+    pub agent_id: String,
+    pub sell_offer_id: String,
+    pub store_link: String,
+    pub product_price: BigDecimal,
+    pub agent_fee: BigDecimal,
+    pub total_paid: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+/// Records the purchase and moves its buy offer to `fulfilled`, marking the
+/// bought sell offer `accepted` and the competing ones `rejected`.
+pub struct ManualBuyHandler;
+
+#[async_trait]
+impl EventHandler for ManualBuyHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "ManualBuy";
+    const TABLES: &'static [&'static str] = &["ManualBuy", "BuyOffer", "SellOffer"];
+
+    type Event = ManualBuyEvent;
+    type Value = ManualBuyValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<ManualBuyValue> {
+        let manual_buy_event: ManualBuyEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed ManualBuy - buy_offer_id: {}, buyer: {}, agent_id: {}, sell_offer_id: {}, store_link: {}, product_price: {}, agent_fee: {}, total_paid: {}, tx: {}",
+            manual_buy_event.buy_offer_id, manual_buy_event.buyer, manual_buy_event.agent_id, manual_buy_event.sell_offer_id, manual_buy_event.store_link, manual_buy_event.product_price, manual_buy_event.agent_fee, manual_buy_event.total_paid, origin.tx_digest
+        );
+
+        let product_price = BigDecimal::from(manual_buy_event.product_price);
+        let agent_fee = BigDecimal::from(manual_buy_event.agent_fee);
+        let total_paid = BigDecimal::from(manual_buy_event.total_paid);
+
+        Ok(ManualBuyValue {
+            buy_offer_id: manual_buy_event.buy_offer_id.to_string(),
+            buyer: manual_buy_event.buyer.to_string(),
+            agent_id: manual_buy_event.agent_id.to_string(),
+            sell_offer_id: manual_buy_event.sell_offer_id.to_string(),
+            store_link: manual_buy_event.store_link,
+            product_price,
+            agent_fee,
+            total_paid,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v ManualBuyValue, batch: &mut InsertBatch<'v>) {
+        batch.manual_buys.push(Cow::Borrowed(value));
+    }
+
+    async fn commit<'a>(value: &ManualBuyValue, conn: &mut Connection<'a>) -> Result<usize> {
+        fulfil_buy_offer(
+            &value.buy_offer_id,
+            Some(value.sell_offer_id.as_str()),
+            &value.total_paid,
+            value.checkpoint,
+            conn,
+        )
+        .await
+    }
+}
+
+/// Move a buy offer still open or expired to `fulfilled`, recording the sell
+/// offer it was bought through, what the buyer paid and the checkpoint it was
+/// bought at. The sell offer is marked `accepted` and the competing ones
+/// `rejected`, as for every kind of buy.
+pub(crate) async fn fulfil_buy_offer(
+    buy_offer_id: &str,
+    sell_offer_id: Option<&str>,
+    total_paid: &BigDecimal,
+    checkpoint: i64,
+    conn: &mut Connection<'_>,
+) -> Result<usize> {
+    let mut count = diesel::update(
+        BuyOffer::table
+            .filter(BuyOffer::buy_offer_id.eq(buy_offer_id))
+            .filter(BuyOffer::status.eq_any([
+                BuyOfferStatus::Open.as_str(),
+                BuyOfferStatus::Expired.as_str(),
+            ])),
+    )
+    .set((
+        BuyOffer::status.eq(BuyOfferStatus::Fulfilled.as_str()),
+        BuyOffer::accepted_sell_offer_id.eq(sell_offer_id),
+        BuyOffer::total_paid.eq(total_paid),
+        BuyOffer::filled_at.eq(checkpoint),
+    ))
+    .execute(conn)
+    .await
+    .map_err(Into::<Error>::into)?;
+
+    // Without the sell offer, the competing ones are left for `BuyOfferDeleted`
+    // to close.
+    let Some(sell_offer_id) = sell_offer_id else {
+        return Ok(count);
+    };
+
+    count += diesel::update(SellOffer::table.filter(SellOffer::sell_offer_id.eq(sell_offer_id)))
+        .set((
+            SellOffer::status.eq(SellOfferStatus::Accepted.as_str()),
+            SellOffer::closed_at.eq(checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    // Sell offers of a time-based offer were closed when it expired, before its
+    // service buy. Sell offers made later in the batch are already inserted;
+    // leave them to their own events.
+    count += diesel::update(
+        SellOffer::table
+            .filter(SellOffer::buy_offer_id.eq(buy_offer_id))
+            .filter(SellOffer::sell_offer_id.ne(sell_offer_id))
+            .filter(SellOffer::status.eq_any([
+                SellOfferStatus::Open.as_str(),
+                SellOfferStatus::Closed.as_str(),
+            ]))
+            .filter(SellOffer::checkpoint.is_null().or(SellOffer::checkpoint.le(checkpoint))),
+    )
+    .set((
+        SellOffer::status.eq(SellOfferStatus::Rejected.as_str()),
+        SellOffer::closed_at.eq(checkpoint),
+    ))
+    .execute(conn)
+    .await
+    .map_err(Into::<Error>::into)?;
+
+    Ok(count)
+}
@@ -0,0 +1,346 @@
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use move_core_types::language_storage::StructTag;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use sui_indexer_alt_framework::postgres::{store::Store, Db};
+use sui_indexer_alt_framework::Result;
+
+use crate::events::batch::InsertBatch;
+use crate::jobs::buy_offer_expiry::BuyOfferExpiryJob;
+use crate::jobs::user_expiry::UserExpiryJob;
+use crate::jobs::CheckpointJob;
+
+pub mod agent_deactivated;
+pub mod agent_history;
+pub mod agent_rating_updated;
+pub mod agent_reactivated;
+pub mod agent_registered;
+pub mod agent_slashed;
+pub mod agent_staked;
+pub mod agent_unstaked;
+pub mod automatic_buy;
+pub mod batch;
+pub mod buy_offer_cancelled;
+pub mod buy_offer_created;
+pub mod buy_offer_deleted;
+pub mod buy_offer_modified;
+pub mod failed_event;
+pub mod manual_buy;
+pub mod raw_event;
+pub mod sell_offer_made;
+pub mod service_buy;
+pub mod shop_purchase;
+pub mod user_registered;
+pub mod user_subscription_cancelled;
+pub mod user_subscription_renewed;
+
+#[cfg(test)]
+mod tests;
+
+/// Module declaring every PriceLess event struct.
+pub const EVENTS_MODULE: &str = "events";
+
+/// Database connection handed to handlers at commit time.
+pub type Connection<'a> = <Db as Store>::Connection<'a>;
+
+/// Identifies the on-chain event a row was indexed from. `(tx_digest, event_seq)`
+/// is unique per event, so replaying a checkpoint range never duplicates rows.
+#[derive(Debug, Clone)]
+pub struct EventOrigin {
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+/// Decodes one Move event type and writes it to the database.
+///
+/// Implementations are registered with an [`EventRegistry`], which matches
+/// events on `MODULE` and `NAME` exactly.
+#[async_trait]
+pub trait EventHandler: Send + Sync + 'static {
+    /// Move module declaring the event struct.
+    const MODULE: &'static str;
+
+    /// Name of the event struct.
+    const NAME: &'static str;
+
+    /// Tables `stage` and `commit` write to, by their name in `schema.rs`. Used to pick
+    /// the events to replay when a table is rebuilt.
+    const TABLES: &'static [&'static str];
+
+    /// Mirror of the Move event struct, used to render archived events as JSON.
+    type Event: Serialize + DeserializeOwned;
+
+    /// Decoded event, carried from `decode` to `commit`.
+    type Value: fmt::Debug + Send + Sync + 'static;
+
+    /// Decode the BCS contents of one event.
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<Self::Value>;
+
+    /// Queue the rows of one decoded event that are only ever inserted. They
+    /// are written for the whole batch before any event is committed.
+    fn stage<'v>(_value: &'v Self::Value, _batch: &mut InsertBatch<'v>) {}
+
+    /// Apply the rest of one decoded event (updates, upserts and derived
+    /// state), in chain order, returning the number of rows affected. Rows
+    /// staged by later events of the batch already exist at this point. Only
+    /// sequential pipelines call it.
+    async fn commit<'a>(_value: &Self::Value, _conn: &mut Connection<'a>) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Render the BCS contents of one event as JSON.
+    fn render(contents: &[u8]) -> Result<serde_json::Value> {
+        let event: Self::Event = bcs::from_bytes(contents)?;
+        Ok(serde_json::to_value(event)?)
+    }
+}
+
+/// A decoded event whose handler type has been erased, so events of different
+/// types can share one batch.
+#[async_trait]
+pub trait Projection: fmt::Debug + Send + Sync {
+    fn stage<'v>(&'v self, batch: &mut InsertBatch<'v>);
+
+    async fn commit<'a>(&self, conn: &mut Connection<'a>) -> Result<usize>;
+}
+
+struct Decoded<H: EventHandler> {
+    value: H::Value,
+}
+
+impl<H: EventHandler> fmt::Debug for Decoded<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[async_trait]
+impl<H: EventHandler> Projection for Decoded<H> {
+    fn stage<'v>(&'v self, batch: &mut InsertBatch<'v>) {
+        H::stage(&self.value, batch)
+    }
+
+    async fn commit<'a>(&self, conn: &mut Connection<'a>) -> Result<usize> {
+        H::commit(&self.value, conn).await
+    }
+}
+
+trait ErasedHandler: Send + Sync {
+    fn module(&self) -> &'static str;
+
+    fn name(&self) -> &'static str;
+
+    fn tables(&self) -> &'static [&'static str];
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<Box<dyn Projection>>;
+
+    fn render(&self, contents: &[u8]) -> Result<serde_json::Value>;
+}
+
+impl<H: EventHandler> ErasedHandler for H {
+    fn module(&self) -> &'static str {
+        H::MODULE
+    }
+
+    fn name(&self) -> &'static str {
+        H::NAME
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        H::TABLES
+    }
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<Box<dyn Projection>> {
+        let value = EventHandler::decode(self, contents, origin)?;
+        Ok(Box::new(Decoded::<H> { value }))
+    }
+
+    fn render(&self, contents: &[u8]) -> Result<serde_json::Value> {
+        H::render(contents)
+    }
+}
+
+/// The set of event handlers and checkpoint jobs the pipelines are built from.
+#[derive(Clone, Default)]
+pub struct EventRegistry {
+    handlers: Vec<Arc<dyn ErasedHandler>>,
+    jobs: Vec<Arc<dyn CheckpointJob>>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with a handler for every event defined in `events.move` and the
+    /// jobs keeping time-based state current.
+    ///
+    /// The agent staking, slashing, (de)activation and rating handlers and the
+    /// subscription renewal and cancellation handlers are left out: the package
+    /// does not emit those events, so their layouts are guesses. Register them
+    /// once the Move structs exist.
+    pub fn priceless() -> Self {
+        Self::new()
+            .register(agent_registered::AgentRegisteredHandler::default())
+            .register(agent_unstaked::AgentUnstakedHandler)
+            .register(user_registered::UserRegisteredHandler)
+            .register(buy_offer_created::BuyOfferCreatedHandler)
+            .register(sell_offer_made::SellOfferMadeHandler)
+            .register(manual_buy::ManualBuyHandler)
+            .register(buy_offer_cancelled::BuyOfferCancelledHandler)
+            .register(buy_offer_deleted::BuyOfferDeletedHandler)
+            .register(buy_offer_modified::BuyOfferModifiedHandler)
+            .register(shop_purchase::ShopPurchaseHandler)
+            .register(service_buy::ServiceBuyHandler)
+            .register(automatic_buy::AutomaticBuyHandler)
+            .schedule(UserExpiryJob)
+            .schedule(BuyOfferExpiryJob)
+    }
+
+    /// Add a handler, replacing any handler already registered for the same
+    /// `(module, struct)` pair.
+    pub fn register<H: EventHandler>(mut self, handler: H) -> Self {
+        self.handlers
+            .retain(|h| h.module() != H::MODULE || h.name() != H::NAME);
+        self.handlers.push(Arc::new(handler));
+        self
+    }
+
+    /// Add a job run on every processed checkpoint, replacing any job with the
+    /// same name.
+    pub fn schedule<J: CheckpointJob>(mut self, job: J) -> Self {
+        self.jobs.retain(|j| j.name() != job.name());
+        self.jobs.push(Arc::new(job));
+        self
+    }
+
+    /// Jobs run on every processed checkpoint, in the order they were scheduled.
+    pub fn jobs(&self) -> impl Iterator<Item = &Arc<dyn CheckpointJob>> + '_ {
+        self.jobs.iter()
+    }
+
+    /// Remove the handler for the event struct called `name`.
+    pub fn disable(&mut self, name: &str) -> Result<()> {
+        let before = self.handlers.len();
+        self.handlers.retain(|h| h.name() != name);
+
+        if self.handlers.len() == before {
+            bail!(
+                "Unknown event type '{name}', expected one of: {}",
+                self.names().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Keep only the handlers for the event structs listed in `names`.
+    pub fn enable_only(&mut self, names: &[String]) -> Result<()> {
+        for name in names {
+            if !self.names().any(|n| n == name) {
+                bail!(
+                    "Unknown event type '{name}', expected one of: {}",
+                    self.names().collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
+        self.handlers.retain(|h| names.iter().any(|n| n == h.name()));
+        Ok(())
+    }
+
+    /// Keep only the handlers and jobs that write to `table`.
+    pub fn only_writing(&mut self, table: &str) -> Result<()> {
+        if !self.tables().any(|t| t == table) {
+            bail!(
+                "No event handler writes to table '{table}', expected one of: {}",
+                self.tables().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        self.handlers.retain(|h| h.tables().contains(&table));
+        self.jobs.retain(|j| j.tables().contains(&table));
+        Ok(())
+    }
+
+    /// The handlers and jobs that write to any of `tables`.
+    pub fn writing_any(&self, tables: &[&str]) -> EventRegistry {
+        let writes = |written: &[&str]| written.iter().any(|t| tables.contains(t));
+
+        EventRegistry {
+            handlers: self.handlers.iter().filter(|h| writes(h.tables())).cloned().collect(),
+            jobs: self.jobs.iter().filter(|j| writes(j.tables())).cloned().collect(),
+        }
+    }
+
+    /// Tables written by the registered handlers and jobs, sorted and deduplicated.
+    pub fn tables(&self) -> impl Iterator<Item = &'static str> {
+        self.handlers
+            .iter()
+            .flat_map(|h| h.tables())
+            .chain(self.jobs.iter().flat_map(|j| j.tables()))
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+    }
+
+    /// Names of the event structs with a registered handler.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.handlers.iter().map(|h| h.name())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Whether a handler is registered for the module and struct of `tag`,
+    /// regardless of the package that defines it.
+    pub fn handles(&self, tag: &StructTag) -> bool {
+        self.find(tag).is_some()
+    }
+
+    fn find(&self, tag: &StructTag) -> Option<&dyn ErasedHandler> {
+        if !tag.type_params.is_empty() {
+            return None;
+        }
+
+        self.handlers
+            .iter()
+            .find(|h| tag.module.as_str() == h.module() && tag.name.as_str() == h.name())
+            .map(|h| h.as_ref())
+    }
+
+    /// Decode an event if a handler is registered for its struct tag. The
+    /// caller is responsible for checking the defining package.
+    pub fn decode(
+        &self,
+        tag: &StructTag,
+        contents: &[u8],
+        origin: &EventOrigin,
+    ) -> Option<Result<Box<dyn Projection>>> {
+        let handler = self.find(tag)?;
+
+        Some(
+            handler
+                .decode(contents, origin)
+                .with_context(|| format!("Failed to decode {} event", handler.name())),
+        )
+    }
+
+    /// Render an event as JSON if a handler is registered for its struct tag.
+    pub fn render(&self, tag: &StructTag, contents: &[u8]) -> Option<Result<serde_json::Value>> {
+        let handler = self.find(tag)?;
+
+        Some(
+            handler
+                .render(contents)
+                .with_context(|| format!("Failed to render {} event as JSON", handler.name())),
+        )
+    }
+}
@@ -0,0 +1,22 @@
+use diesel::prelude::*;
+use sui_indexer_alt_framework::FieldCount;
+
+use crate::schema::RawEvent;
+
+/// An event from a configured PriceLess package, exactly as emitted. `json` is
+/// the decoded contents, or `None` when no handler is registered for the type.
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = RawEvent)]
+pub struct RawEventValue {
+    pub package_id: String,
+    pub module: String,
+    pub struct_name: String,
+    pub event_type: String,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub sender: String,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+    pub bcs: Vec<u8>,
+    pub json: Option<serde_json::Value>,
+}
@@ -0,0 +1,177 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::{BuyOffer, SellOffer, SellOfferRevision};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SellOfferMadeEvent {
+    pub buy_offer_id: ObjectID,
+    pub sell_offer_id: ObjectID,
+    pub agent_id: ObjectID,
+    pub agent_address: SuiAddress,
+    pub store_link: String,
+    pub price: u64,
+    pub is_update: bool,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = SellOffer)]
+pub struct SellOfferValue {
+    pub buy_offer_id: String,
+    pub sell_offer_id: String,
+    pub agent_id: String,
+    pub agent_address: String,
+    pub store_link: String,
+    pub price: BigDecimal,
+    pub is_update: bool,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = SellOfferRevision)]
+pub struct SellOfferRevisionValue {
+    pub sell_offer_id: String,
+    pub buy_offer_id: String,
+    pub agent_id: String,
+    pub agent_address: String,
+    pub store_link: String,
+    pub price: BigDecimal,
+    pub is_update: bool,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+impl SellOfferValue {
+    pub fn revision(&self) -> SellOfferRevisionValue {
+        SellOfferRevisionValue {
+            sell_offer_id: self.sell_offer_id.clone(),
+            buy_offer_id: self.buy_offer_id.clone(),
+            agent_id: self.agent_id.clone(),
+            agent_address: self.agent_address.clone(),
+            store_link: self.store_link.clone(),
+            price: self.price.clone(),
+            is_update: self.is_update,
+            tx_digest: self.tx_digest.clone(),
+            event_seq: self.event_seq,
+            checkpoint: self.checkpoint,
+            checkpoint_timestamp_ms: self.checkpoint_timestamp_ms,
+        }
+    }
+}
+
+/// Appends every sell offer to `SellOfferRevision` and keeps one `SellOffer`
+/// row per `sell_offer_id` holding its latest revision.
+pub struct SellOfferMadeHandler;
+
+#[async_trait]
+impl EventHandler for SellOfferMadeHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "SellOfferMade";
+    const TABLES: &'static [&'static str] = &["SellOfferRevision", "SellOffer"];
+
+    type Event = SellOfferMadeEvent;
+    type Value = SellOfferValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<SellOfferValue> {
+        let sell_offer_event: SellOfferMadeEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed SellOfferMade - buy_offer_id: {}, sell_offer_id: {}, agent_id: {}, agent_address: {}, store_link: {}, price: {}, is_update: {}, tx: {}",
+            sell_offer_event.buy_offer_id, sell_offer_event.sell_offer_id, sell_offer_event.agent_id, sell_offer_event.agent_address, sell_offer_event.store_link, sell_offer_event.price, sell_offer_event.is_update, origin.tx_digest
+        );
+
+        let price = BigDecimal::from(sell_offer_event.price);
+
+        Ok(SellOfferValue {
+            buy_offer_id: sell_offer_event.buy_offer_id.to_string(),
+            sell_offer_id: sell_offer_event.sell_offer_id.to_string(),
+            agent_id: sell_offer_event.agent_id.to_string(),
+            agent_address: sell_offer_event.agent_address.to_string(),
+            store_link: sell_offer_event.store_link,
+            price,
+            is_update: sell_offer_event.is_update,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+            updated_at: origin.checkpoint,
+        })
+    }
+
+    fn stage<'v>(value: &'v SellOfferValue, batch: &mut InsertBatch<'v>) {
+        batch.sell_offer_revisions.push(Cow::Owned(value.revision()));
+
+        if !value.is_update {
+            batch.sell_offers.push(Cow::Borrowed(value));
+        }
+    }
+
+    // Revisions update the current row in place, so they are applied in order,
+    // and one older than the row's latest is not applied again on replay. The
+    // original offer keeps the provenance of the event that created it.
+    async fn commit<'a>(value: &SellOfferValue, conn: &mut Connection<'a>) -> Result<usize> {
+        if !value.is_update {
+            return Ok(0);
+        }
+
+        if indexed_buy_offers(&[value.buy_offer_id.as_str()], conn).await?.is_empty() {
+            warn!(
+                "Skipping SellOffer {} revised in {}: its BuyOffer {} was never indexed",
+                value.sell_offer_id, value.tx_digest, value.buy_offer_id
+            );
+            return Ok(0);
+        }
+
+        diesel::insert_into(SellOffer::table)
+            .values(value)
+            .on_conflict(SellOffer::sell_offer_id)
+            .do_update()
+            .set((
+                SellOffer::agent_address.eq(&value.agent_address),
+                SellOffer::store_link.eq(&value.store_link),
+                SellOffer::price.eq(&value.price),
+                SellOffer::is_update.eq(true),
+                SellOffer::updated_at.eq(value.updated_at),
+            ))
+            .filter(SellOffer::updated_at.is_null().or(SellOffer::updated_at.le(value.updated_at)))
+            .execute(conn)
+            .await
+            .map_err(Into::<Error>::into)
+    }
+}
+
+/// The ids among `buy_offer_ids` with an indexed `BuyOffer`. A sell offer on
+/// any other buy offer, e.g. one whose `BuyOfferCreated` failed to decode,
+/// would break the foreign key between them and stop the offers pipeline, so
+/// it is skipped; its event stays in the `RawEvent` archive.
+pub(crate) async fn indexed_buy_offers<'a>(
+    buy_offer_ids: &[&str],
+    conn: &mut Connection<'a>,
+) -> Result<BTreeSet<String>> {
+    let ids: Vec<String> = BuyOffer::table
+        .filter(BuyOffer::buy_offer_id.eq_any(buy_offer_ids))
+        .select(BuyOffer::buy_offer_id)
+        .load(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    Ok(ids.into_iter().collect())
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::{EventHandler, EventOrigin, EVENTS_MODULE};
+
+/// Emitted when the monitor service buys a time-based offer after its deadline.
+/// It is followed by `AutomaticBuy`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ServiceBuyEvent {
+    pub buy_offer_id: ObjectID,
+    pub buyer: SuiAddress,
+    pub agent_id: ObjectID,
+    pub sell_offer_id: ObjectID,
+    pub store_link: String,
+    pub product_price: u64,
+    pub agent_fee: u64,
+    pub platform_fee: u64,
+    pub buyer_savings: u64,
+}
+
+/// Only archived in `RawEvent`: the `AutomaticBuy` that always follows it in
+/// the same transaction fulfils the offer.
+pub struct ServiceBuyHandler;
+
+#[async_trait]
+impl EventHandler for ServiceBuyHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "ServiceBuy";
+    const TABLES: &'static [&'static str] = &[];
+
+    type Event = ServiceBuyEvent;
+    type Value = ServiceBuyEvent;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<ServiceBuyEvent> {
+        let service_buy_event: ServiceBuyEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed ServiceBuy - buy_offer_id: {}, buyer: {}, agent_id: {}, sell_offer_id: {}, product_price: {}, tx: {}",
+            service_buy_event.buy_offer_id, service_buy_event.buyer, service_buy_event.agent_id, service_buy_event.sell_offer_id, service_buy_event.product_price, origin.tx_digest
+        );
+
+        Ok(service_buy_event)
+    }
+}
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::ObjectID;
+
+use crate::events::batch::InsertBatch;
+use crate::events::{EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::ShopPurchase;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShopPurchaseEvent {
+    pub agent_id: ObjectID,
+    pub store_link: String,
+    pub product_price: u64,
+    pub agent_fee: u64,
+    pub platform_fee: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = ShopPurchase)]
+pub struct ShopPurchaseValue {
+    pub agent_id: String,
+    pub store_link: String,
+    pub product_price: BigDecimal,
+    pub agent_fee: BigDecimal,
+    pub platform_fee: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+/// Records every purchase in `ShopPurchase`. Agent statistics are computed from
+/// these rows when agents are read, so purchases need no ordering.
+pub struct ShopPurchaseHandler;
+
+#[async_trait]
+impl EventHandler for ShopPurchaseHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "ShopPurchase";
+    const TABLES: &'static [&'static str] = &["ShopPurchase"];
+
+    type Event = ShopPurchaseEvent;
+    type Value = ShopPurchaseValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<ShopPurchaseValue> {
+        let shop_purchase_event: ShopPurchaseEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed ShopPurchase - agent_id: {}, store_link: {}, product_price: {}, agent_fee: {}, platform_fee: {}, tx: {}",
+            shop_purchase_event.agent_id, shop_purchase_event.store_link, shop_purchase_event.product_price, shop_purchase_event.agent_fee, shop_purchase_event.platform_fee, origin.tx_digest
+        );
+
+        let product_price = BigDecimal::from(shop_purchase_event.product_price);
+        let agent_fee = BigDecimal::from(shop_purchase_event.agent_fee);
+        let platform_fee = BigDecimal::from(shop_purchase_event.platform_fee);
+
+        Ok(ShopPurchaseValue {
+            agent_id: shop_purchase_event.agent_id.to_string(),
+            store_link: shop_purchase_event.store_link,
+            product_price,
+            agent_fee,
+            platform_fee,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v ShopPurchaseValue, batch: &mut InsertBatch<'v>) {
+        batch.shop_purchases.push(Cow::Borrowed(value));
+    }
+}
@@ -0,0 +1,459 @@
+use bigdecimal::BigDecimal;
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::agent_registered::AgentRegisteredHandler;
+use crate::events::agent_unstaked::AgentUnstakedHandler;
+use crate::events::automatic_buy::AutomaticBuyHandler;
+use crate::events::buy_offer_cancelled::BuyOfferCancelledHandler;
+use crate::events::buy_offer_created::BuyOfferCreatedHandler;
+use crate::events::buy_offer_deleted::BuyOfferDeletedHandler;
+use crate::events::buy_offer_modified::BuyOfferModifiedHandler;
+use crate::events::manual_buy::ManualBuyHandler;
+use crate::events::sell_offer_made::SellOfferMadeHandler;
+use crate::events::service_buy::ServiceBuyHandler;
+use crate::events::shop_purchase::ShopPurchaseHandler;
+use crate::events::user_registered::UserRegisteredHandler;
+use crate::events::{ErasedHandler, EventHandler, EventOrigin, EventRegistry, EVENTS_MODULE};
+use crate::jobs::CheckpointJob;
+use crate::status::BuyOfferStatus;
+
+fn origin() -> EventOrigin {
+    EventOrigin {
+        tx_digest: "digest".to_string(),
+        event_seq: 3,
+        checkpoint: 100,
+        checkpoint_timestamp_ms: 1_700_000_000_000,
+    }
+}
+
+fn id_bytes(byte: u8) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[31] = byte;
+    bytes
+}
+
+fn id(byte: u8) -> ObjectID {
+    ObjectID::new(id_bytes(byte))
+}
+
+fn address(byte: u8) -> SuiAddress {
+    SuiAddress::from(id(byte))
+}
+
+/// BCS contents written field by field in the order the Move struct in
+/// `contracts/priceless/sources/events.move` declares them, so decoding is
+/// checked against the on-chain layout rather than against the Rust mirror.
+#[derive(Default)]
+struct MoveEvent(Vec<u8>);
+
+impl MoveEvent {
+    /// An `ID` or `address` ending in `byte`.
+    fn id(mut self, byte: u8) -> Self {
+        self.0.extend(id_bytes(byte));
+        self
+    }
+
+    fn address(self, byte: u8) -> Self {
+        self.id(byte)
+    }
+
+    fn u64(mut self, value: u64) -> Self {
+        self.0.extend(value.to_le_bytes());
+        self
+    }
+
+    fn bool(mut self, value: bool) -> Self {
+        self.0.push(value as u8);
+        self
+    }
+
+    /// A `std::string::String`: its ULEB128 length, then its UTF-8 bytes.
+    fn string(mut self, value: &str) -> Self {
+        let mut len = value.len();
+        while len >= 0x80 {
+            self.0.push((len as u8 & 0x7f) | 0x80);
+            len >>= 7;
+        }
+        self.0.push(len as u8);
+        self.0.extend(value.as_bytes());
+        self
+    }
+
+    fn decode<H: EventHandler>(&self, handler: &H) -> H::Value {
+        EventHandler::decode(handler, &self.0, &origin()).unwrap()
+    }
+}
+
+fn tag(module: &str, name: &str) -> StructTag {
+    StructTag {
+        address: AccountAddress::ONE,
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn names(registry: &EventRegistry) -> Vec<&'static str> {
+    let mut names: Vec<_> = registry.names().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn decodes_agent_registered() {
+    let value = MoveEvent::default()
+        .id(1)
+        .address(2)
+        .address(3)
+        .u64(500)
+        .u64(42)
+        .decode(&AgentRegisteredHandler { initial_rating: 7 });
+
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.agent_address, address(2).to_string());
+    assert_eq!(value.agent_owner_address, address(3).to_string());
+    assert_eq!(value.stake_amount, BigDecimal::from(500));
+    assert_eq!(value.rating, BigDecimal::from(7));
+    assert!(value.active);
+    assert_eq!(value.registered_at, BigDecimal::from(42));
+    assert_eq!((value.tx_digest.as_str(), value.event_seq, value.checkpoint), ("digest", 3, 100));
+}
+
+#[test]
+fn decodes_agent_unstaked() {
+    let value = MoveEvent::default()
+        .id(1)
+        .address(2)
+        .u64(500)
+        .u64(42)
+        .decode(&AgentUnstakedHandler);
+
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.change, "unstaked");
+    assert_eq!(value.stake_delta, Some(BigDecimal::from(-500)));
+    assert_eq!(value.stake_amount, Some(BigDecimal::from(0)));
+    assert_eq!(value.active, Some(false));
+    assert_eq!(value.changed_at, BigDecimal::from(42));
+}
+
+#[test]
+fn decodes_user_registered() {
+    let value = MoveEvent::default()
+        .id(1)
+        .address(2)
+        .address(3)
+        .u64(10)
+        .u64(2_000_000_000_000)
+        .u64(42)
+        .decode(&UserRegisteredHandler);
+
+    assert_eq!(value.user_id, id(1).to_string());
+    assert_eq!(value.user_address, address(2).to_string());
+    assert_eq!(value.user_owner_address, address(3).to_string());
+    assert_eq!(value.subscription_fee, BigDecimal::from(10));
+    assert_eq!(value.subscription_deadline, BigDecimal::from(2_000_000_000_000u64));
+    assert_eq!(value.registered_at, BigDecimal::from(42));
+}
+
+#[test]
+fn decodes_buy_offer_created() {
+    let value = MoveEvent::default()
+        .id(4)
+        .address(3)
+        .string("Headphones")
+        .u64(1_000)
+        .bool(true)
+        .u64(2_000_000_000_000)
+        .u64(42)
+        .decode(&BuyOfferCreatedHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.owner, address(3).to_string());
+    assert_eq!(value.product, "Headphones");
+    assert_eq!(value.price, BigDecimal::from(1_000));
+    assert!(value.offer_type_is_time_based);
+    assert_eq!(value.deadline, BigDecimal::from(2_000_000_000_000u64));
+    assert_eq!(value.created_at, BigDecimal::from(42));
+}
+
+#[test]
+fn decodes_buy_offer_modified() {
+    let value = MoveEvent::default()
+        .id(4)
+        .address(3)
+        .u64(1_000)
+        .u64(800)
+        .u64(200)
+        .decode(&BuyOfferModifiedHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.old_price, BigDecimal::from(1_000));
+    assert_eq!(value.new_price, BigDecimal::from(800));
+    assert_eq!(value.price_reduction, BigDecimal::from(200));
+}
+
+#[test]
+fn decodes_buy_offer_deleted() {
+    let value = MoveEvent::default().id(4).address(3).u64(0).decode(&BuyOfferDeletedHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.remaining_balance, BigDecimal::from(0));
+    assert_eq!(value.checkpoint, 100);
+}
+
+#[test]
+fn decodes_buy_offer_cancelled() {
+    let value = MoveEvent::default().id(4).address(3).u64(1_000).decode(&BuyOfferCancelledHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.refunded_amount, BigDecimal::from(1_000));
+    assert_eq!(value.checkpoint, 100);
+}
+
+#[test]
+fn deleted_offers_close_by_remaining_balance() {
+    let deleted = |balance| MoveEvent::default().id(4).address(3).u64(balance).decode(&BuyOfferDeletedHandler);
+
+    assert_eq!(deleted(0).closing_status(), BuyOfferStatus::Fulfilled);
+    assert_eq!(deleted(1_000).closing_status(), BuyOfferStatus::Cancelled);
+}
+
+#[test]
+fn decodes_sell_offer_made() {
+    let value = MoveEvent::default()
+        .id(4)
+        .id(5)
+        .id(1)
+        .address(2)
+        .string("https://example.com/item")
+        .u64(900)
+        .bool(true)
+        .decode(&SellOfferMadeHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.sell_offer_id, id(5).to_string());
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.agent_address, address(2).to_string());
+    assert_eq!(value.store_link, "https://example.com/item");
+    assert_eq!(value.price, BigDecimal::from(900));
+    assert!(value.is_update);
+}
+
+#[test]
+fn decodes_manual_buy() {
+    let value = MoveEvent::default()
+        .id(4)
+        .address(3)
+        .id(1)
+        .id(5)
+        .string("https://example.com/item")
+        .u64(900)
+        .u64(45)
+        .u64(945)
+        .decode(&ManualBuyHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.buyer, address(3).to_string());
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.sell_offer_id, id(5).to_string());
+    assert_eq!(value.store_link, "https://example.com/item");
+    assert_eq!(value.product_price, BigDecimal::from(900));
+    assert_eq!(value.agent_fee, BigDecimal::from(45));
+    assert_eq!(value.total_paid, BigDecimal::from(945));
+}
+
+#[test]
+fn decodes_automatic_buy() {
+    let value = MoveEvent::default()
+        .id(4)
+        .address(3)
+        .id(1)
+        .string("https://example.com/item")
+        .u64(900)
+        .u64(45)
+        .u64(9)
+        .u64(0)
+        .decode(&AutomaticBuyHandler);
+
+    assert_eq!(value.buy_offer_id, id(4).to_string());
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.total_paid, BigDecimal::from(954));
+    assert_eq!(value.checkpoint, 100);
+}
+
+fn service_buy() -> MoveEvent {
+    MoveEvent::default()
+        .id(4)
+        .address(3)
+        .id(1)
+        .id(2)
+        .string("https://example.com/item")
+        .u64(900)
+        .u64(45)
+        .u64(9)
+        .u64(91)
+}
+
+#[test]
+fn decodes_service_buy() {
+    let value = service_buy().decode(&ServiceBuyHandler);
+
+    assert_eq!(value.buy_offer_id, id(4));
+    assert_eq!(value.sell_offer_id, id(2));
+    assert_eq!(value.store_link, "https://example.com/item");
+    assert_eq!((value.product_price, value.agent_fee, value.platform_fee, value.buyer_savings), (900, 45, 9, 91));
+}
+
+#[test]
+fn renders_service_buy() {
+    let json = ServiceBuyHandler::render(&service_buy().0).unwrap();
+
+    assert_eq!(json["agent_id"], serde_json::to_value(id(1)).unwrap());
+    assert_eq!(json["sell_offer_id"], serde_json::to_value(id(2)).unwrap());
+    assert_eq!(json["buyer_savings"], 91);
+}
+
+#[test]
+fn decodes_shop_purchase() {
+    let value = MoveEvent::default()
+        .id(1)
+        .string("https://example.com/item")
+        .u64(900)
+        .u64(45)
+        .u64(9)
+        .decode(&ShopPurchaseHandler);
+
+    assert_eq!(value.agent_id, id(1).to_string());
+    assert_eq!(value.store_link, "https://example.com/item");
+    assert_eq!(value.product_price, BigDecimal::from(900));
+    assert_eq!(value.agent_fee, BigDecimal::from(45));
+    assert_eq!(value.platform_fee, BigDecimal::from(9));
+}
+
+#[test]
+fn decodes_long_strings() {
+    // A length of 200 takes two ULEB128 bytes.
+    let product = "x".repeat(200);
+    let value = MoveEvent::default()
+        .id(4)
+        .address(3)
+        .string(&product)
+        .u64(1_000)
+        .bool(false)
+        .u64(0)
+        .u64(42)
+        .decode(&BuyOfferCreatedHandler);
+
+    assert_eq!(value.product, product);
+}
+
+#[test]
+fn decode_rejects_truncated_or_trailing_contents() {
+    let MoveEvent(contents) = MoveEvent::default().id(4).address(3).u64(0);
+
+    let truncated = &contents[..contents.len() - 1];
+    assert!(EventHandler::decode(&BuyOfferDeletedHandler, truncated, &origin()).is_err());
+
+    let mut trailing = contents.clone();
+    trailing.push(0);
+    assert!(EventHandler::decode(&BuyOfferDeletedHandler, &trailing, &origin()).is_err());
+}
+
+#[test]
+fn registry_find_matches_module_and_name() {
+    let registry = EventRegistry::priceless();
+
+    let handler = registry.find(&tag(EVENTS_MODULE, "BuyOfferCreated")).unwrap();
+    assert_eq!(handler.name(), "BuyOfferCreated");
+    assert_eq!(handler.module(), EVENTS_MODULE);
+
+    assert!(registry.find(&tag("other", "BuyOfferCreated")).is_none());
+    assert!(registry.find(&tag(EVENTS_MODULE, "BuyOfferCreatedV2")).is_none());
+
+    let mut generic = tag(EVENTS_MODULE, "BuyOfferCreated");
+    generic.type_params.push(TypeTag::U64);
+    assert!(registry.find(&generic).is_none());
+}
+
+#[test]
+fn registry_register_replaces_the_handler_for_the_same_event() {
+    let registry = EventRegistry::new()
+        .register(AgentRegisteredHandler { initial_rating: 1 })
+        .register(AgentRegisteredHandler { initial_rating: 7 });
+
+    assert_eq!(names(&registry), ["AgentRegistered"]);
+}
+
+#[test]
+fn registry_disable_removes_one_handler() {
+    let mut registry = EventRegistry::priceless();
+    let before = registry.names().count();
+
+    registry.disable("ShopPurchase").unwrap();
+
+    assert_eq!(registry.names().count(), before - 1);
+    assert!(!registry.handles(&tag(EVENTS_MODULE, "ShopPurchase")));
+    assert!(registry.handles(&tag(EVENTS_MODULE, "ManualBuy")));
+
+    // Already removed, so no longer a known event type.
+    assert!(registry.disable("ShopPurchase").is_err());
+    assert!(registry.disable("NoSuchEvent").is_err());
+}
+
+#[test]
+fn registry_enable_only_keeps_listed_handlers() {
+    let mut registry = EventRegistry::priceless();
+
+    registry
+        .enable_only(&["UserRegistered".to_string(), "BuyOfferCreated".to_string()])
+        .unwrap();
+
+    assert_eq!(names(&registry), ["BuyOfferCreated", "UserRegistered"]);
+
+    // Jobs are kept; an unknown name leaves the registry untouched.
+    assert_eq!(registry.jobs().count(), 2);
+    assert!(registry.enable_only(&["NoSuchEvent".to_string()]).is_err());
+    assert_eq!(names(&registry), ["BuyOfferCreated", "UserRegistered"]);
+}
+
+#[test]
+fn default_registry_only_handles_events_defined_in_move() {
+    let events_move = include_str!("../../../contracts/priceless/sources/events.move");
+
+    for name in EventRegistry::priceless().names() {
+        assert!(
+            events_move.contains(&format!("public struct {name} has")),
+            "{name} is not defined in events.move"
+        );
+    }
+}
+
+#[test]
+fn registry_writing_any_selects_handlers_and_jobs() {
+    let registry = EventRegistry::priceless();
+
+    let users = registry.writing_any(&["User"]);
+    assert_eq!(names(&users), ["UserRegistered"]);
+    assert_eq!(users.jobs().map(|j| j.name()).collect::<Vec<_>>(), ["UserExpiry"]);
+
+    let offers = registry.writing_any(&["BuyOffer", "ShopPurchase"]);
+    assert_eq!(
+        names(&offers),
+        [
+            "AutomaticBuy",
+            "BuyOfferCancelled",
+            "BuyOfferCreated",
+            "BuyOfferDeleted",
+            "BuyOfferModified",
+            "ManualBuy",
+            "ShopPurchase",
+        ]
+    );
+    assert_eq!(offers.jobs().map(|j| j.name()).collect::<Vec<_>>(), ["BuyOfferExpiry"]);
+
+    // Handlers writing no table are never selected.
+    assert!(registry.writing_any(&[]).is_empty());
+}
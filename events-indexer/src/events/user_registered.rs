@@ -0,0 +1,82 @@
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use sui_indexer_alt_framework::FieldCount;
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::batch::InsertBatch;
+use crate::events::{EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::User;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserRegisteredEvent {
+    pub user_id: ObjectID,
+    pub user_object_address: SuiAddress,
+    pub user_owner_address: SuiAddress,
+    pub subscription_fee: u64,
+    pub subscription_deadline: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Insertable, Debug, Clone, FieldCount)]
+#[diesel(table_name = User)]
+pub struct UserValue {
+    pub user_id: String,
+    pub user_address: String,
+    pub user_owner_address: String,
+    pub subscription_fee: BigDecimal,
+    pub subscription_deadline: BigDecimal,
+    pub active: bool,
+    pub registered_at: BigDecimal,
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub checkpoint: i64,
+    pub checkpoint_timestamp_ms: i64,
+}
+
+pub struct UserRegisteredHandler;
+
+#[async_trait]
+impl EventHandler for UserRegisteredHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "UserRegistered";
+    const TABLES: &'static [&'static str] = &["User"];
+
+    type Event = UserRegisteredEvent;
+    type Value = UserValue;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<UserValue> {
+        let user_event: UserRegisteredEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed UserRegistered - user_id: {}, object_address: {}, owner_address: {}, subscription_fee: {}, subscription_deadline: {}, timestamp: {}, tx: {}",
+            user_event.user_id, user_event.user_object_address, user_event.user_owner_address, user_event.subscription_fee, user_event.subscription_deadline, user_event.timestamp, origin.tx_digest
+        );
+
+        let subscription_fee = BigDecimal::from(user_event.subscription_fee);
+        let subscription_deadline = BigDecimal::from(user_event.subscription_deadline);
+        let registered_at = BigDecimal::from(user_event.timestamp);
+
+        Ok(UserValue {
+            user_id: user_event.user_id.to_string(),
+            user_address: user_event.user_object_address.to_string(),
+            user_owner_address: user_event.user_owner_address.to_string(),
+            subscription_fee,
+            subscription_deadline,
+            active: true,
+            registered_at,
+            tx_digest: origin.tx_digest.clone(),
+            event_seq: origin.event_seq,
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+        })
+    }
+
+    fn stage<'v>(value: &'v UserValue, batch: &mut InsertBatch<'v>) {
+        batch.users.push(Cow::Borrowed(value));
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::User;
+
+/// Emitted when a user ends their subscription before the deadline.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserSubscriptionCancelledEvent {
+    pub user_id: ObjectID,
+    pub user_owner_address: SuiAddress,
+    pub timestamp: u64,
+}
+
+#[derive(Debug)]
+pub struct UserSubscriptionCancelledData {
+    pub user_id: String,
+    pub cancelled_at: BigDecimal,
+    pub checkpoint: i64,
+}
+
+/// Not registered by default, as the package has no `UserSubscriptionCancelled`
+/// event yet.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct UserSubscriptionCancelledHandler;
+
+#[async_trait]
+impl EventHandler for UserSubscriptionCancelledHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "UserSubscriptionCancelled";
+    const TABLES: &'static [&'static str] = &["User"];
+
+    type Event = UserSubscriptionCancelledEvent;
+    type Value = UserSubscriptionCancelledData;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<UserSubscriptionCancelledData> {
+        let cancelled_event: UserSubscriptionCancelledEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed UserSubscriptionCancelled - user_id: {}, owner_address: {}, timestamp: {}, tx: {}",
+            cancelled_event.user_id, cancelled_event.user_owner_address, cancelled_event.timestamp, origin.tx_digest
+        );
+
+        let cancelled_at = BigDecimal::from(cancelled_event.timestamp);
+
+        Ok(UserSubscriptionCancelledData {
+            user_id: cancelled_event.user_id.to_string(),
+            cancelled_at,
+            checkpoint: origin.checkpoint,
+        })
+    }
+
+    async fn commit<'a>(value: &UserSubscriptionCancelledData, conn: &mut Connection<'a>) -> Result<usize> {
+        diesel::update(
+            User::table
+                .filter(User::user_id.eq(&value.user_id))
+                .filter(User::updated_at.is_null().or(User::updated_at.le(value.checkpoint))),
+        )
+        .set((
+            User::active.eq(false),
+            User::subscription_cancelled_at.eq(&value.cancelled_at),
+            User::updated_at.eq(value.checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
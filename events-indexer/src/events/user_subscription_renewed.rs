@@ -0,0 +1,87 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use log::info;
+use serde::{Deserialize, Serialize};
+use sui_indexer_alt_framework::Result;
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::events::{Connection, EventHandler, EventOrigin, EVENTS_MODULE};
+use crate::schema::User;
+
+/// Emitted when a user pays for another subscription period.
+/// `subscription_deadline` is the new deadline.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserSubscriptionRenewedEvent {
+    pub user_id: ObjectID,
+    pub user_owner_address: SuiAddress,
+    pub subscription_fee: u64,
+    pub subscription_deadline: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Debug)]
+pub struct UserSubscriptionRenewedData {
+    pub user_id: String,
+    pub subscription_fee: BigDecimal,
+    pub subscription_deadline: BigDecimal,
+    /// Whether the subscription is still running at the event's checkpoint,
+    /// matching the on-chain `timestamp < subscription_deadline` check.
+    pub active: bool,
+    pub checkpoint: i64,
+}
+
+/// Not registered by default: subscriptions are only paid at registration today.
+/// See [`EventRegistry::priceless`](crate::events::EventRegistry::priceless).
+pub struct UserSubscriptionRenewedHandler;
+
+#[async_trait]
+impl EventHandler for UserSubscriptionRenewedHandler {
+    const MODULE: &'static str = EVENTS_MODULE;
+    const NAME: &'static str = "UserSubscriptionRenewed";
+    const TABLES: &'static [&'static str] = &["User"];
+
+    type Event = UserSubscriptionRenewedEvent;
+    type Value = UserSubscriptionRenewedData;
+
+    fn decode(&self, contents: &[u8], origin: &EventOrigin) -> Result<UserSubscriptionRenewedData> {
+        let renewed_event: UserSubscriptionRenewedEvent = bcs::from_bytes(contents)?;
+
+        info!(
+            "Successfully parsed UserSubscriptionRenewed - user_id: {}, owner_address: {}, subscription_fee: {}, subscription_deadline: {}, timestamp: {}, tx: {}",
+            renewed_event.user_id, renewed_event.user_owner_address, renewed_event.subscription_fee, renewed_event.subscription_deadline, renewed_event.timestamp, origin.tx_digest
+        );
+
+        let subscription_fee = BigDecimal::from(renewed_event.subscription_fee);
+        let subscription_deadline = BigDecimal::from(renewed_event.subscription_deadline);
+
+        Ok(UserSubscriptionRenewedData {
+            user_id: renewed_event.user_id.to_string(),
+            subscription_fee,
+            subscription_deadline,
+            active: i128::from(origin.checkpoint_timestamp_ms)
+                < i128::from(renewed_event.subscription_deadline),
+            checkpoint: origin.checkpoint,
+        })
+    }
+
+    async fn commit<'a>(value: &UserSubscriptionRenewedData, conn: &mut Connection<'a>) -> Result<usize> {
+        diesel::update(
+            User::table
+                .filter(User::user_id.eq(&value.user_id))
+                .filter(User::updated_at.is_null().or(User::updated_at.le(value.checkpoint))),
+        )
+        .set((
+            User::subscription_fee.eq(&value.subscription_fee),
+            User::subscription_deadline.eq(&value.subscription_deadline),
+            User::active.eq(value.active),
+            User::subscription_cancelled_at.eq(None::<BigDecimal>),
+            User::updated_at.eq(value.checkpoint),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
@@ -0,0 +1,363 @@
+//! Batched lookups behind the relations of the GraphQL schema. Every relation
+//! resolved while executing a query is collected and fetched with one
+//! statement per key type, instead of one per parent row. Lists under a parent
+//! take at most `first` rows per parent in the same statement.
+
+use async_graphql::dataloader::Loader;
+use diesel::sql_types::{Array, BigInt, Nullable, Text};
+use diesel_async::RunQueryDsl;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+use sui_indexer_alt_framework::postgres::store::Store;
+use sui_indexer_alt_framework::postgres::Db;
+
+use super::{BuyOfferOrder, PurchaseOrder, SellOfferOrder};
+use crate::handlers::{EventTables, ShopPurchases};
+use crate::models::{Agent, BuyOffer, SellOffer, ShopPurchase, User};
+use crate::queries;
+
+pub struct DbLoader {
+    pub db: Db,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AgentId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BuyOfferId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SellOfferId(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OwnerAddress(pub String);
+
+/// The first `first` sell offers made on a buy offer in `order`, optionally
+/// only those with `status`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SellOffersOf {
+    pub buy_offer_id: String,
+    pub status: Option<String>,
+    pub order: SellOfferOrder,
+    pub first: i64,
+}
+
+/// The first `first` sell offers made by an agent in `order`, optionally only
+/// those with `status`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SellOffersBy {
+    pub agent_id: String,
+    pub status: Option<String>,
+    pub order: SellOfferOrder,
+    pub first: i64,
+}
+
+/// The first `first` buy offers created by the user at `owner` in `order`,
+/// optionally only those with `status`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BuyOffersBy {
+    pub owner: String,
+    pub status: Option<String>,
+    pub order: BuyOfferOrder,
+    pub first: i64,
+}
+
+/// The first `first` shop purchases made through an agent in `order`, up to
+/// the `shop_purchases` pipeline's watermark.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShopPurchasesBy {
+    pub agent_id: String,
+    pub order: PurchaseOrder,
+    pub first: i64,
+}
+
+impl Loader<AgentId> for DbLoader {
+    type Value = Agent;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[AgentId]) -> Result<HashMap<AgentId, Agent>, Self::Error> {
+        let ids: Vec<String> = keys.iter().map(|k| k.0.clone()).collect();
+        let mut conn = self.db.connect().await?;
+        let rows = queries::agents_by_ids(&mut conn, &ids).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (AgentId(row.agent_id.clone()), row))
+            .collect())
+    }
+}
+
+impl Loader<BuyOfferId> for DbLoader {
+    type Value = BuyOffer;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[BuyOfferId]) -> Result<HashMap<BuyOfferId, BuyOffer>, Self::Error> {
+        let ids: Vec<String> = keys.iter().map(|k| k.0.clone()).collect();
+        let mut conn = self.db.connect().await?;
+        let rows = queries::buy_offers_by_ids(&mut conn, &ids).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (BuyOfferId(row.buy_offer_id.clone()), row))
+            .collect())
+    }
+}
+
+impl Loader<SellOfferId> for DbLoader {
+    type Value = SellOffer;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[SellOfferId]) -> Result<HashMap<SellOfferId, SellOffer>, Self::Error> {
+        let ids: Vec<String> = keys.iter().map(|k| k.0.clone()).collect();
+        let mut conn = self.db.connect().await?;
+        let rows = queries::sell_offers_by_ids(&mut conn, &ids).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (SellOfferId(row.sell_offer_id.clone()), row))
+            .collect())
+    }
+}
+
+impl Loader<OwnerAddress> for DbLoader {
+    type Value = User;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[OwnerAddress]) -> Result<HashMap<OwnerAddress, User>, Self::Error> {
+        let addresses: Vec<String> = keys.iter().map(|k| k.0.clone()).collect();
+        let mut conn = self.db.connect().await?;
+        let rows = queries::users_by_owner_addresses(&mut conn, &addresses).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (OwnerAddress(row.user_owner_address.clone()), row))
+            .collect())
+    }
+}
+
+impl Loader<SellOffersOf> for DbLoader {
+    type Value = Vec<SellOffer>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[SellOffersOf]) -> Result<HashMap<SellOffersOf, Vec<SellOffer>>, Self::Error> {
+        let mut conn = self.db.connect().await?;
+        let groups = by_arguments(keys, |k| ((k.status.clone(), k.order, k.first), k.buy_offer_id.clone()));
+
+        let mut offers: HashMap<SellOffersOf, Vec<SellOffer>> = HashMap::new();
+        for ((status, order, first), buy_offer_ids) in groups {
+            let query = first_per_parent(
+                "SellOffer",
+                "buy_offer_id",
+                "($3::TEXT IS NULL OR status = $3)",
+                order.order_by(),
+            );
+
+            let rows: Vec<SellOffer> = diesel::sql_query(query)
+                .bind::<Array<Text>, _>(buy_offer_ids)
+                .bind::<BigInt, _>(first)
+                .bind::<Nullable<Text>, _>(status.clone())
+                .load(&mut conn)
+                .await
+                .map_err(Into::<anyhow::Error>::into)?;
+
+            for row in rows {
+                let key = SellOffersOf {
+                    buy_offer_id: row.buy_offer_id.clone(),
+                    status: status.clone(),
+                    order,
+                    first,
+                };
+                offers.entry(key).or_default().push(row);
+            }
+        }
+
+        Ok(offers)
+    }
+}
+
+impl Loader<SellOffersBy> for DbLoader {
+    type Value = Vec<SellOffer>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[SellOffersBy]) -> Result<HashMap<SellOffersBy, Vec<SellOffer>>, Self::Error> {
+        let mut conn = self.db.connect().await?;
+        let groups = by_arguments(keys, |k| ((k.status.clone(), k.order, k.first), k.agent_id.clone()));
+
+        let mut offers: HashMap<SellOffersBy, Vec<SellOffer>> = HashMap::new();
+        for ((status, order, first), agent_ids) in groups {
+            let query = first_per_parent(
+                "SellOffer",
+                "agent_id",
+                "($3::TEXT IS NULL OR status = $3)",
+                order.order_by(),
+            );
+
+            let rows: Vec<SellOffer> = diesel::sql_query(query)
+                .bind::<Array<Text>, _>(agent_ids)
+                .bind::<BigInt, _>(first)
+                .bind::<Nullable<Text>, _>(status.clone())
+                .load(&mut conn)
+                .await
+                .map_err(Into::<anyhow::Error>::into)?;
+
+            for row in rows {
+                let key = SellOffersBy {
+                    agent_id: row.agent_id.clone(),
+                    status: status.clone(),
+                    order,
+                    first,
+                };
+                offers.entry(key).or_default().push(row);
+            }
+        }
+
+        Ok(offers)
+    }
+}
+
+impl Loader<BuyOffersBy> for DbLoader {
+    type Value = Vec<BuyOffer>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[BuyOffersBy]) -> Result<HashMap<BuyOffersBy, Vec<BuyOffer>>, Self::Error> {
+        let mut conn = self.db.connect().await?;
+        let groups = by_arguments(keys, |k| ((k.status.clone(), k.order, k.first), k.owner.clone()));
+
+        let mut offers: HashMap<BuyOffersBy, Vec<BuyOffer>> = HashMap::new();
+        for ((status, order, first), owners) in groups {
+            let query = first_per_parent(
+                "BuyOffer",
+                "owner",
+                "($3::TEXT IS NULL OR status = $3)",
+                order.order_by(),
+            );
+
+            let rows: Vec<BuyOffer> = diesel::sql_query(query)
+                .bind::<Array<Text>, _>(owners)
+                .bind::<BigInt, _>(first)
+                .bind::<Nullable<Text>, _>(status.clone())
+                .load(&mut conn)
+                .await
+                .map_err(Into::<anyhow::Error>::into)?;
+
+            for row in rows {
+                let key = BuyOffersBy {
+                    owner: row.owner.clone(),
+                    status: status.clone(),
+                    order,
+                    first,
+                };
+                offers.entry(key).or_default().push(row);
+            }
+        }
+
+        Ok(offers)
+    }
+}
+
+impl Loader<ShopPurchasesBy> for DbLoader {
+    type Value = Vec<ShopPurchase>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(&self, keys: &[ShopPurchasesBy]) -> Result<HashMap<ShopPurchasesBy, Vec<ShopPurchase>>, Self::Error> {
+        let mut conn = self.db.connect().await?;
+        let Some(watermark) = queries::committed_checkpoint(&mut conn, ShopPurchases::NAME).await? else {
+            return Ok(HashMap::new());
+        };
+
+        let groups = by_arguments(keys, |k| ((k.order, k.first), k.agent_id.clone()));
+
+        let mut purchases: HashMap<ShopPurchasesBy, Vec<ShopPurchase>> = HashMap::new();
+        for ((order, first), agent_ids) in groups {
+            let query = first_per_parent(
+                "ShopPurchase",
+                "agent_id",
+                "(checkpoint IS NULL OR checkpoint <= $3)",
+                order.order_by(),
+            );
+
+            let rows: Vec<ShopPurchase> = diesel::sql_query(query)
+                .bind::<Array<Text>, _>(agent_ids)
+                .bind::<BigInt, _>(first)
+                .bind::<BigInt, _>(watermark)
+                .load(&mut conn)
+                .await
+                .map_err(Into::<anyhow::Error>::into)?;
+
+            for row in rows {
+                let key = ShopPurchasesBy {
+                    agent_id: row.agent_id.clone(),
+                    order,
+                    first,
+                };
+                purchases.entry(key).or_default().push(row);
+            }
+        }
+
+        Ok(purchases)
+    }
+}
+
+impl SellOfferOrder {
+    fn order_by(self) -> &'static str {
+        match self {
+            SellOfferOrder::Oldest => "id ASC",
+            SellOfferOrder::Newest => "id DESC",
+            SellOfferOrder::PriceAsc => "price ASC, id ASC",
+            SellOfferOrder::PriceDesc => "price DESC, id ASC",
+        }
+    }
+}
+
+impl BuyOfferOrder {
+    fn order_by(self) -> &'static str {
+        match self {
+            BuyOfferOrder::Oldest => "id ASC",
+            BuyOfferOrder::Newest => "id DESC",
+            BuyOfferOrder::PriceAsc => "price ASC, id ASC",
+            BuyOfferOrder::PriceDesc => "price DESC, id ASC",
+            BuyOfferOrder::DeadlineAsc => "deadline ASC, id ASC",
+        }
+    }
+}
+
+impl PurchaseOrder {
+    fn order_by(self) -> &'static str {
+        match self {
+            PurchaseOrder::Oldest => "checkpoint ASC NULLS FIRST, id ASC",
+            PurchaseOrder::Newest => "checkpoint DESC NULLS LAST, id DESC",
+            PurchaseOrder::PriceAsc => "product_price ASC, id ASC",
+            PurchaseOrder::PriceDesc => "product_price DESC, id ASC",
+        }
+    }
+}
+
+/// Parent ids of `keys`, grouped by the rest of each key. Keys loaded by one
+/// field share its arguments, so there is usually a single group.
+fn by_arguments<K, A: Eq + Hash>(keys: &[K], split: impl Fn(&K) -> (A, String)) -> HashMap<A, Vec<String>> {
+    let mut groups: HashMap<A, Vec<String>> = HashMap::new();
+    for key in keys {
+        let (arguments, parent) = split(key);
+        groups.entry(arguments).or_default().push(parent);
+    }
+
+    groups
+}
+
+/// Rows of `table` whose `parent` is one of `$1` and that meet `condition`,
+/// at most `$2` per parent in `order_by`. Within each parent, rows come back
+/// in that order.
+fn first_per_parent(table: &str, parent: &str, condition: &str, order_by: &str) -> String {
+    format!(
+        r#"
+        SELECT * FROM (
+            SELECT *, ROW_NUMBER() OVER (PARTITION BY {parent} ORDER BY {order_by}) AS position
+            FROM "{table}"
+            WHERE {parent} = ANY($1) AND {condition}
+        ) ranked
+        WHERE position <= $2
+        ORDER BY position
+        "#
+    )
+}
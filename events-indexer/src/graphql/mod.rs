@@ -0,0 +1,700 @@
+//! GraphQL schema over the projection tables, built with the `graphql` feature
+//! and served by `events-api` at `/graphql`.
+//!
+//! Each table is an object type with its rows' columns and its relations.
+//! Top-level lists take a filter and an order, and are paginated as Relay
+//! connections whose cursors are the key each row is ordered by, so rows
+//! written between two pages do not shift the second. Relations are batched
+//! with [`loaders::DbLoader`]; those to several rows take the first `first`
+//! rows of each parent.
+
+use anyhow::anyhow;
+use async_graphql::connection::{query, Connection, CursorType, Edge};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Enum, Object, OutputType, Result,
+    Schema,
+};
+use async_graphql_axum::GraphQL;
+use axum::response::{Html, IntoResponse};
+use axum::routing::get;
+use axum::Router;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable};
+use diesel_async::RunQueryDsl;
+use std::fmt::Display;
+use std::future::Future;
+use std::str::FromStr;
+use sui_indexer_alt_framework::postgres::store::Store;
+use sui_indexer_alt_framework::postgres::Db;
+
+use crate::handlers::{EventTables, ManualBuys, ShopPurchases};
+use crate::models::{Agent, BuyOffer, ManualBuy, SellOffer, ShopPurchase, User};
+use crate::queries::{
+    self, AgentFilter, BuyOfferFilter, ManualBuyFilter, SellOfferFilter, ShopPurchaseFilter,
+    UserFilter, DEFAULT_PAGE_SIZE,
+};
+use crate::schema;
+use crate::server::MAX_PAGE_SIZE;
+
+use self::loaders::{
+    AgentId, BuyOfferId, BuyOffersBy, DbLoader, OwnerAddress, SellOfferId, SellOffersBy, SellOffersOf,
+    ShopPurchasesBy,
+};
+
+pub mod loaders;
+
+/// Deepest nesting a query may use, e.g. buy offer → sell offer → agent →
+/// sell offers → buy offer.
+const MAX_DEPTH: usize = 10;
+
+/// Highest cost a query may have. Each field costs 1, and the fields under a
+/// list once per row it may return, so a full page of 200 rows can select
+/// about 25 fields.
+const MAX_COMPLEXITY: usize = 5_000;
+
+pub type PriceLessSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn schema(db: Db) -> PriceLessSchema {
+    let loader = DataLoader::new(DbLoader { db: db.clone() }, tokio::spawn);
+
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(db)
+        .data(loader)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+/// `/graphql`: queries over POST, GraphiQL over GET.
+pub fn router(db: Db) -> Router {
+    Router::new().route(
+        "/graphql",
+        get(graphiql).post_service(GraphQL::new(schema(db))),
+    )
+}
+
+async fn graphiql() -> impl IntoResponse {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuyOfferOrder {
+    Oldest,
+    Newest,
+    PriceAsc,
+    PriceDesc,
+    /// Soonest deadline first; only meaningful for time-based offers.
+    DeadlineAsc,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SellOfferOrder {
+    Oldest,
+    Newest,
+    PriceAsc,
+    PriceDesc,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AgentOrder {
+    Oldest,
+    Newest,
+    RatingDesc,
+    StakeDesc,
+    BuysDesc,
+    VolumeDesc,
+}
+
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UserOrder {
+    Oldest,
+    Newest,
+}
+
+/// Order of manual buys and shop purchases.
+#[derive(Enum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PurchaseOrder {
+    /// Earliest checkpoint first.
+    Oldest,
+    /// Latest checkpoint first.
+    Newest,
+    PriceAsc,
+    PriceDesc,
+}
+
+diesel::define_sql_function! {
+    /// `x`, or `y` when it is null.
+    fn coalesce(x: Nullable<BigInt>, y: BigInt) -> BigInt;
+}
+
+/// Orders `$query` and keeps the rows after the cursor `$after`, if any. The
+/// order is `$id` alone, or `$key` then `$id`, where `$key` is a `$value` in
+/// cursors. Each `$order` is `asc` or `desc`, with `$cmp` the matching `gt`
+/// or `lt`.
+macro_rules! keyset {
+    ($query:expr, $after:expr, $id:expr, $order:ident, $cmp:ident) => {{
+        let query = $query.order($id.$order());
+        match $after {
+            Some(after) => query.filter($id.$cmp(after.id)),
+            None => query,
+        }
+    }};
+
+    (
+        $query:expr, $after:expr,
+        $key:expr => $value:ty, $key_order:ident, $key_cmp:ident,
+        $id:expr, $order:ident, $cmp:ident
+    ) => {{
+        let query = $query.order(($key.$key_order(), $id.$order()));
+        match $after {
+            Some(after) => {
+                let value: $value = after.value()?;
+                query.filter(
+                    $key.$key_cmp(value.clone())
+                        .or($key.eq(value).and($id.$cmp(after.id))),
+                )
+            }
+            None => query,
+        }
+    }};
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn buy_offer(&self, ctx: &Context<'_>, buy_offer_id: String) -> Result<Option<BuyOffer>> {
+        Ok(loader(ctx)?.load_one(BuyOfferId(buy_offer_id)).await?)
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn buy_offers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<BuyOfferFilter>,
+        order_by: Option<BuyOfferOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, BuyOffer>> {
+        buy_offers(ctx, filter.unwrap_or_default(), order_by, after, first).await
+    }
+
+    async fn sell_offer(&self, ctx: &Context<'_>, sell_offer_id: String) -> Result<Option<SellOffer>> {
+        Ok(loader(ctx)?.load_one(SellOfferId(sell_offer_id)).await?)
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn sell_offers(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<SellOfferFilter>,
+        order_by: Option<SellOfferOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, SellOffer>> {
+        sell_offers(ctx, filter.unwrap_or_default(), order_by, after, first).await
+    }
+
+    async fn agent(&self, ctx: &Context<'_>, agent_id: String) -> Result<Option<Agent>> {
+        Ok(loader(ctx)?.load_one(AgentId(agent_id)).await?)
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn agents(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AgentFilter>,
+        order_by: Option<AgentOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, Agent>> {
+        let db = ctx.data::<Db>()?;
+        let filter = filter.unwrap_or_default();
+        let order = order_by.unwrap_or(AgentOrder::Oldest);
+
+        let cursor = move |row: &Agent| match order {
+            AgentOrder::Oldest | AgentOrder::Newest => RowCursor::by_id(row.id),
+            AgentOrder::RatingDesc => RowCursor::by(&row.rating, row.id),
+            AgentOrder::StakeDesc => RowCursor::by(&row.stake_amount, row.id),
+            AgentOrder::BuysDesc => RowCursor::by(row.buys, row.id),
+            AgentOrder::VolumeDesc => RowCursor::by(&row.total_volume, row.id),
+        };
+
+        paginate(after, first, cursor, |after, limit| async move {
+            use schema::AgentWithStats::dsl;
+
+            let query = queries::agents_matching(&filter);
+            let query = match order {
+                AgentOrder::Oldest => keyset!(query, after, dsl::id, asc, gt),
+                AgentOrder::Newest => keyset!(query, after, dsl::id, desc, lt),
+                AgentOrder::RatingDesc => {
+                    keyset!(query, after, dsl::rating => BigDecimal, desc, lt, dsl::id, asc, gt)
+                }
+                AgentOrder::StakeDesc => {
+                    keyset!(query, after, dsl::stake_amount => BigDecimal, desc, lt, dsl::id, asc, gt)
+                }
+                AgentOrder::BuysDesc => {
+                    keyset!(query, after, dsl::buys => i64, desc, lt, dsl::id, asc, gt)
+                }
+                AgentOrder::VolumeDesc => {
+                    keyset!(query, after, dsl::total_volume => BigDecimal, desc, lt, dsl::id, asc, gt)
+                }
+            };
+
+            let mut conn = db.connect().await?;
+            Ok(query.limit(limit).load(&mut conn).await?)
+        })
+        .await
+    }
+
+    /// The user registered by `owner_address`.
+    async fn user(&self, ctx: &Context<'_>, owner_address: String) -> Result<Option<User>> {
+        Ok(loader(ctx)?.load_one(OwnerAddress(owner_address)).await?)
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<UserFilter>,
+        order_by: Option<UserOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, User>> {
+        let db = ctx.data::<Db>()?;
+        let filter = filter.unwrap_or_default();
+
+        let cursor = |row: &User| RowCursor::by_id(row.id);
+
+        paginate(after, first, cursor, |after, limit| async move {
+            use schema::User::dsl;
+
+            let query = queries::users_matching(&filter);
+            let query = match order_by.unwrap_or(UserOrder::Oldest) {
+                UserOrder::Oldest => keyset!(query, after, dsl::id, asc, gt),
+                UserOrder::Newest => keyset!(query, after, dsl::id, desc, lt),
+            };
+
+            let mut conn = db.connect().await?;
+            Ok(query.limit(limit).load(&mut conn).await?)
+        })
+        .await
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn manual_buys(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ManualBuyFilter>,
+        order_by: Option<PurchaseOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, ManualBuy>> {
+        let db = ctx.data::<Db>()?;
+        let filter = filter.unwrap_or_default();
+        let order = order_by.unwrap_or(PurchaseOrder::Oldest);
+        let cursor = move |row: &ManualBuy| order.cursor(row.checkpoint_key(), &row.product_price);
+
+        paginate(after, first, cursor, |after, limit| async move {
+            use schema::ManualBuy::dsl;
+
+            let mut conn = db.connect().await?;
+            let Some(watermark) = queries::committed_checkpoint(&mut conn, ManualBuys::NAME).await? else {
+                return Ok(vec![]);
+            };
+
+            let query = queries::manual_buys_matching(&filter)
+                .filter(dsl::checkpoint.is_null().or(dsl::checkpoint.le(watermark)));
+            let query = match order {
+                PurchaseOrder::Oldest => keyset!(
+                    query, after,
+                    coalesce(dsl::checkpoint, -1_i64) => i64, asc, gt,
+                    dsl::id, asc, gt
+                ),
+                PurchaseOrder::Newest => keyset!(
+                    query, after,
+                    coalesce(dsl::checkpoint, -1_i64) => i64, desc, lt,
+                    dsl::id, desc, lt
+                ),
+                PurchaseOrder::PriceAsc => {
+                    keyset!(query, after, dsl::product_price => BigDecimal, asc, gt, dsl::id, asc, gt)
+                }
+                PurchaseOrder::PriceDesc => {
+                    keyset!(query, after, dsl::product_price => BigDecimal, desc, lt, dsl::id, asc, gt)
+                }
+            };
+
+            Ok(query.limit(limit).load(&mut conn).await?)
+        })
+        .await
+    }
+
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn shop_purchases(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ShopPurchaseFilter>,
+        order_by: Option<PurchaseOrder>,
+        after: Option<String>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Connection<RowCursor, ShopPurchase>> {
+        shop_purchases(ctx, filter.unwrap_or_default(), order_by, after, first).await
+    }
+}
+
+#[ComplexObject]
+impl BuyOffer {
+    /// The user who created the offer.
+    async fn user(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        Ok(loader(ctx)?.load_one(OwnerAddress(self.owner.clone())).await?)
+    }
+
+    /// The first `first` sell offers made on this offer, cheapest first unless
+    /// ordered otherwise, optionally only those with the given `status`. Page
+    /// through them all with the top-level `sellOffers`.
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn sell_offers(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        order_by: Option<SellOfferOrder>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Vec<SellOffer>> {
+        let key = SellOffersOf {
+            buy_offer_id: self.buy_offer_id.clone(),
+            status,
+            order: order_by.unwrap_or(SellOfferOrder::PriceAsc),
+            first: page_size(first),
+        };
+
+        Ok(loader(ctx)?.load_one(key).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl SellOffer {
+    async fn buy_offer(&self, ctx: &Context<'_>) -> Result<Option<BuyOffer>> {
+        Ok(loader(ctx)?.load_one(BuyOfferId(self.buy_offer_id.clone())).await?)
+    }
+
+    async fn agent(&self, ctx: &Context<'_>) -> Result<Option<Agent>> {
+        Ok(loader(ctx)?.load_one(AgentId(self.agent_id.clone())).await?)
+    }
+}
+
+#[ComplexObject]
+impl Agent {
+    /// The first `first` sell offers made by this agent, optionally only those
+    /// with the given `status`. Page through them all with the top-level
+    /// `sellOffers`.
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn sell_offers(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        order_by: Option<SellOfferOrder>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Vec<SellOffer>> {
+        let key = SellOffersBy {
+            agent_id: self.agent_id.clone(),
+            status,
+            order: order_by.unwrap_or(SellOfferOrder::Oldest),
+            first: page_size(first),
+        };
+
+        Ok(loader(ctx)?.load_one(key).await?.unwrap_or_default())
+    }
+
+    /// The first `first` purchases made through this agent, manual or
+    /// automatic. Page through them all with the top-level `shopPurchases`.
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn shop_purchases(
+        &self,
+        ctx: &Context<'_>,
+        order_by: Option<PurchaseOrder>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Vec<ShopPurchase>> {
+        let key = ShopPurchasesBy {
+            agent_id: self.agent_id.clone(),
+            order: order_by.unwrap_or(PurchaseOrder::Oldest),
+            first: page_size(first),
+        };
+
+        Ok(loader(ctx)?.load_one(key).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl User {
+    /// The first `first` buy offers created by this user, optionally only
+    /// those with the given `status`. Page through them all with the
+    /// top-level `buyOffers`.
+    #[graphql(complexity = "page_size(first) as usize * child_complexity")]
+    async fn buy_offers(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        order_by: Option<BuyOfferOrder>,
+        #[graphql(validator(minimum = 0))] first: Option<i32>,
+    ) -> Result<Vec<BuyOffer>> {
+        let key = BuyOffersBy {
+            owner: self.user_owner_address.clone(),
+            status,
+            order: order_by.unwrap_or(BuyOfferOrder::Oldest),
+            first: page_size(first),
+        };
+
+        Ok(loader(ctx)?.load_one(key).await?.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl ManualBuy {
+    async fn buy_offer(&self, ctx: &Context<'_>) -> Result<Option<BuyOffer>> {
+        Ok(loader(ctx)?.load_one(BuyOfferId(self.buy_offer_id.clone())).await?)
+    }
+
+    async fn sell_offer(&self, ctx: &Context<'_>) -> Result<Option<SellOffer>> {
+        Ok(loader(ctx)?.load_one(SellOfferId(self.sell_offer_id.clone())).await?)
+    }
+
+    async fn agent(&self, ctx: &Context<'_>) -> Result<Option<Agent>> {
+        Ok(loader(ctx)?.load_one(AgentId(self.agent_id.clone())).await?)
+    }
+}
+
+#[ComplexObject]
+impl ShopPurchase {
+    async fn agent(&self, ctx: &Context<'_>) -> Result<Option<Agent>> {
+        Ok(loader(ctx)?.load_one(AgentId(self.agent_id.clone())).await?)
+    }
+}
+
+fn loader<'c>(ctx: &Context<'c>) -> Result<&'c DataLoader<DbLoader>> {
+    ctx.data::<DataLoader<DbLoader>>()
+}
+
+/// Rows to return for `first`: the default page size when unset, and never
+/// more than [`MAX_PAGE_SIZE`]. Negative values are rejected by the argument's
+/// validator, but complexity is computed before that, so they count as none.
+fn page_size(first: Option<i32>) -> i64 {
+    first.map_or(DEFAULT_PAGE_SIZE, i64::from).clamp(0, MAX_PAGE_SIZE)
+}
+
+async fn buy_offers(
+    ctx: &Context<'_>,
+    filter: BuyOfferFilter,
+    order_by: Option<BuyOfferOrder>,
+    after: Option<String>,
+    first: Option<i32>,
+) -> Result<Connection<RowCursor, BuyOffer>> {
+    let db = ctx.data::<Db>()?;
+    let order = order_by.unwrap_or(BuyOfferOrder::Oldest);
+
+    let cursor = move |row: &BuyOffer| match order {
+        BuyOfferOrder::Oldest | BuyOfferOrder::Newest => RowCursor::by_id(row.id),
+        BuyOfferOrder::PriceAsc | BuyOfferOrder::PriceDesc => RowCursor::by(&row.price, row.id),
+        BuyOfferOrder::DeadlineAsc => RowCursor::by(&row.deadline, row.id),
+    };
+
+    paginate(after, first, cursor, |after, limit| async move {
+        use schema::BuyOffer::dsl;
+
+        let query = queries::buy_offers_matching(&filter);
+        let query = match order {
+            BuyOfferOrder::Oldest => keyset!(query, after, dsl::id, asc, gt),
+            BuyOfferOrder::Newest => keyset!(query, after, dsl::id, desc, lt),
+            BuyOfferOrder::PriceAsc => {
+                keyset!(query, after, dsl::price => BigDecimal, asc, gt, dsl::id, asc, gt)
+            }
+            BuyOfferOrder::PriceDesc => {
+                keyset!(query, after, dsl::price => BigDecimal, desc, lt, dsl::id, asc, gt)
+            }
+            BuyOfferOrder::DeadlineAsc => {
+                keyset!(query, after, dsl::deadline => BigDecimal, asc, gt, dsl::id, asc, gt)
+            }
+        };
+
+        let mut conn = db.connect().await?;
+        Ok(query.limit(limit).load(&mut conn).await?)
+    })
+    .await
+}
+
+async fn sell_offers(
+    ctx: &Context<'_>,
+    filter: SellOfferFilter,
+    order_by: Option<SellOfferOrder>,
+    after: Option<String>,
+    first: Option<i32>,
+) -> Result<Connection<RowCursor, SellOffer>> {
+    let db = ctx.data::<Db>()?;
+    let order = order_by.unwrap_or(SellOfferOrder::Oldest);
+
+    let cursor = move |row: &SellOffer| match order {
+        SellOfferOrder::Oldest | SellOfferOrder::Newest => RowCursor::by_id(row.id),
+        SellOfferOrder::PriceAsc | SellOfferOrder::PriceDesc => RowCursor::by(&row.price, row.id),
+    };
+
+    paginate(after, first, cursor, |after, limit| async move {
+        use schema::SellOffer::dsl;
+
+        let query = queries::sell_offers_matching(&filter);
+        let query = match order {
+            SellOfferOrder::Oldest => keyset!(query, after, dsl::id, asc, gt),
+            SellOfferOrder::Newest => keyset!(query, after, dsl::id, desc, lt),
+            SellOfferOrder::PriceAsc => {
+                keyset!(query, after, dsl::price => BigDecimal, asc, gt, dsl::id, asc, gt)
+            }
+            SellOfferOrder::PriceDesc => {
+                keyset!(query, after, dsl::price => BigDecimal, desc, lt, dsl::id, asc, gt)
+            }
+        };
+
+        let mut conn = db.connect().await?;
+        Ok(query.limit(limit).load(&mut conn).await?)
+    })
+    .await
+}
+
+async fn shop_purchases(
+    ctx: &Context<'_>,
+    filter: ShopPurchaseFilter,
+    order_by: Option<PurchaseOrder>,
+    after: Option<String>,
+    first: Option<i32>,
+) -> Result<Connection<RowCursor, ShopPurchase>> {
+    let db = ctx.data::<Db>()?;
+    let order = order_by.unwrap_or(PurchaseOrder::Oldest);
+    let cursor = move |row: &ShopPurchase| order.cursor(row.checkpoint_key(), &row.product_price);
+
+    paginate(after, first, cursor, |after, limit| async move {
+        use schema::ShopPurchase::dsl;
+
+        let mut conn = db.connect().await?;
+        let Some(watermark) = queries::committed_checkpoint(&mut conn, ShopPurchases::NAME).await? else {
+            return Ok(vec![]);
+        };
+
+        let query = queries::shop_purchases_matching(&filter)
+            .filter(dsl::checkpoint.is_null().or(dsl::checkpoint.le(watermark)));
+        let query = match order {
+            PurchaseOrder::Oldest => keyset!(
+                query, after,
+                coalesce(dsl::checkpoint, -1_i64) => i64, asc, gt,
+                dsl::id, asc, gt
+            ),
+            PurchaseOrder::Newest => keyset!(
+                query, after,
+                coalesce(dsl::checkpoint, -1_i64) => i64, desc, lt,
+                dsl::id, desc, lt
+            ),
+            PurchaseOrder::PriceAsc => {
+                keyset!(query, after, dsl::product_price => BigDecimal, asc, gt, dsl::id, asc, gt)
+            }
+            PurchaseOrder::PriceDesc => {
+                keyset!(query, after, dsl::product_price => BigDecimal, desc, lt, dsl::id, asc, gt)
+            }
+        };
+
+        Ok(query.limit(limit).load(&mut conn).await?)
+    })
+    .await
+}
+
+/// A connection over the rows `fetch` returns for the cursor to start after
+/// and a limit, paginated forwards from `after`. Each row's cursor is
+/// `cursor` of it.
+async fn paginate<T, F, Fut>(
+    after: Option<String>,
+    first: Option<i32>,
+    cursor: impl Fn(&T) -> RowCursor,
+    fetch: F,
+) -> Result<Connection<RowCursor, T>>
+where
+    T: OutputType,
+    F: FnOnce(Option<RowCursor>, i64) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<T>>>,
+{
+    let limit = page_size(first) as usize;
+
+    query(after, None, first, None, |after: Option<RowCursor>, _: Option<RowCursor>, _, _| async move {
+        let has_previous_page = after.is_some();
+
+        // One row more than asked for tells whether there is a next page.
+        let mut rows = fetch(after, limit as i64 + 1).await?;
+        let has_next_page = rows.len() > limit;
+        rows.truncate(limit);
+
+        let mut connection = Connection::new(has_previous_page, has_next_page);
+        connection.edges.extend(rows.into_iter().map(|row| Edge::new(cursor(&row), row)));
+
+        Ok::<_, async_graphql::Error>(connection)
+    })
+    .await
+}
+
+/// Position of a row in a top-level list: the value the list is ordered by,
+/// then the row's `id`, encoded as `value:id`. Lists ordered by `id` alone
+/// encode just the `id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowCursor {
+    value: Option<String>,
+    id: i32,
+}
+
+impl RowCursor {
+    fn by_id(id: i32) -> Self {
+        Self { value: None, id }
+    }
+
+    fn by(value: impl Display, id: i32) -> Self {
+        Self {
+            value: Some(value.to_string()),
+            id,
+        }
+    }
+
+    /// The value the row is ordered by, which fails for a cursor taken from a
+    /// list in another order.
+    fn value<V: FromStr>(&self) -> anyhow::Result<V> {
+        self.value
+            .as_deref()
+            .and_then(|value| value.parse().ok())
+            .ok_or_else(|| anyhow!("Cursor '{}' is not from a list in this order", self.encode_cursor()))
+    }
+}
+
+impl CursorType for RowCursor {
+    type Error = anyhow::Error;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let (value, id) = match s.rsplit_once(':') {
+            Some((value, id)) => (Some(value.to_string()), id),
+            None => (None, s),
+        };
+
+        let id = id.parse().map_err(|_| anyhow!("Invalid cursor '{s}'"))?;
+        Ok(Self { value, id })
+    }
+
+    fn encode_cursor(&self) -> String {
+        match &self.value {
+            Some(value) => format!("{value}:{}", self.id),
+            None => self.id.to_string(),
+        }
+    }
+}
+
+impl PurchaseOrder {
+    /// Cursor of a purchase with `checkpoint_key` and `price` in this order.
+    fn cursor(self, (checkpoint, id): (i64, i32), price: &BigDecimal) -> RowCursor {
+        match self {
+            PurchaseOrder::Oldest | PurchaseOrder::Newest => RowCursor::by(checkpoint, id),
+            PurchaseOrder::PriceAsc | PurchaseOrder::PriceDesc => RowCursor::by(price, id),
+        }
+    }
+}
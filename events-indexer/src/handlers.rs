@@ -1,239 +1,335 @@
-use anyhow::{Context, Error};
+use anyhow::Context;
 use async_trait::async_trait;
-use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
-use log::{error, info};
-use serde::Deserialize;
-use std::sync::Arc;
-use sui_indexer_alt_framework::pipeline::sequential::Handler;
+use log::{error, info, warn};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use sui_indexer_alt_framework::pipeline::concurrent::Handler as ConcurrentHandler;
+use sui_indexer_alt_framework::pipeline::sequential::Handler as SequentialHandler;
 use sui_indexer_alt_framework::pipeline::Processor;
 use sui_indexer_alt_framework::postgres::{store::Store, Db};
 use sui_indexer_alt_framework::types::full_checkpoint_content::CheckpointData;
 use sui_indexer_alt_framework::FieldCount;
 use sui_indexer_alt_framework::Result;
-use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::base_types::ObjectID;
 use sui_types::event::Event;
 
-use crate::schema::{Agent, User, BuyOffer, SellOffer, ManualBuy, ShopPurchase};
+use crate::config::{DecodeErrorPolicy, PackageConfig};
+use crate::events::agent_history::AgentHistoryValue;
+use crate::events::agent_registered::AgentValue;
+use crate::events::batch::InsertBatch;
+use crate::events::failed_event::FailedEventValue;
+use crate::events::raw_event::RawEventValue;
+use crate::events::{Connection, EventOrigin, EventRegistry, Projection};
+use crate::jobs::{CheckpointJob, CheckpointTime};
+
+// ============== PIPELINE TABLES ==============
+
+/// The tables one pipeline writes, under the name its watermark is kept as.
+/// Every table belongs to exactly one pipeline, and no pipeline writes to
+/// another's tables.
+pub trait EventTables: Send + Sync + 'static {
+    const NAME: &'static str;
+    const TABLES: &'static [&'static str];
+}
 
-// ============== EVENT DEFINITIONS ==============
+/// Buy and sell offers, whose status follows the order of events. Sequential.
+pub struct Offers;
 
-#[derive(Deserialize, Debug)]
-pub struct AgentRegisteredEvent {
-    pub agent_id: ObjectID,
-    pub agent_object_address: SuiAddress,
-    pub agent_owner_address: SuiAddress,
-    pub stake_amount: u64,
-    pub timestamp: u64,
-}
+/// Agents and their history. Sequential.
+pub struct Agents;
 
-#[derive(Deserialize, Debug)]
-pub struct UserRegisteredEvent {
-    pub user_id: ObjectID,
-    pub user_object_address: SuiAddress,
-    pub user_owner_address: SuiAddress,
-    pub subscription_fee: u64,
-    pub subscription_deadline: u64,
-    pub timestamp: u64,
-}
+/// Users and their subscriptions. Sequential.
+pub struct Users;
 
-#[derive(Deserialize, Debug)]
-pub struct BuyOfferCreatedEvent {
-    pub buy_offer_id: ObjectID,
-    pub owner: SuiAddress,
-    pub product: String,
-    pub price: u64,
-    pub offer_type_is_time_based: bool,
-    pub deadline: u64,
-    pub timestamp: u64,
-}
+/// Append-only record of manual buys. Concurrent.
+pub struct ManualBuys;
 
-#[derive(Deserialize, Debug)]
-pub struct SellOfferMadeEvent {
-    pub buy_offer_id: ObjectID,
-    pub sell_offer_id: ObjectID,
-    pub agent_id: ObjectID,
-    pub agent_address: SuiAddress,
-    pub store_link: String,
-    pub price: u64,
-    pub is_update: bool,
-}
+/// Append-only record of shop purchases. Concurrent.
+pub struct ShopPurchases;
 
-#[derive(Deserialize, Debug)]
-pub struct ManualBuyEvent {
-    pub buy_offer_id: ObjectID,
-    pub buyer: SuiAddress,
-    pub agent_id: ObjectID,
-    pub sell_offer_id: ObjectID,
-    pub store_link: String,
-    pub product_price: u64,
-    pub agent_fee: u64,
-    pub total_paid: u64,
+impl EventTables for Offers {
+    const NAME: &'static str = "offers";
+    const TABLES: &'static [&'static str] = &["BuyOffer", "BuyOfferPriceChange", "SellOffer", "SellOfferRevision"];
 }
 
-#[derive(Deserialize, Debug)]
-pub struct BuyOfferDeletedEvent {
-    pub buy_offer_id: ObjectID,
-    pub owner: SuiAddress,
-    pub remaining_balance: u64,
+impl EventTables for Agents {
+    const NAME: &'static str = "agents";
+    const TABLES: &'static [&'static str] = &["Agent", "AgentHistory"];
 }
 
-#[derive(Deserialize, Debug)]
-pub struct BuyOfferModifiedEvent {
-    pub buy_offer_id: ObjectID,
-    pub owner: SuiAddress,
-    pub old_price: u64,
-    pub new_price: u64,
-    pub price_reduction: u64,
+impl EventTables for Users {
+    const NAME: &'static str = "users";
+    const TABLES: &'static [&'static str] = &["User"];
 }
 
-#[derive(Deserialize, Debug)]
-pub struct ShopPurchaseEvent {
-    pub agent_id: ObjectID,
-    pub store_link: String,
-    pub product_price: u64,
-    pub agent_fee: u64,
-    pub platform_fee: u64,
+impl EventTables for ManualBuys {
+    const NAME: &'static str = "manual_buys";
+    const TABLES: &'static [&'static str] = &["ManualBuy"];
 }
 
-// ============== DATABASE VALUE TYPES ==============
-
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = Agent)]
-pub struct AgentValue {
-    pub agent_id: String,
-    pub agent_address: String,
-    pub agent_owner_address: String,
-    pub stake_amount: i64,
-    pub rating: i64,
-    pub buys: i64,
-    pub active: bool,
-    pub registered_at: i64,
+impl EventTables for ShopPurchases {
+    const NAME: &'static str = "shop_purchases";
+    const TABLES: &'static [&'static str] = &["ShopPurchase"];
 }
 
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = User)]
-pub struct UserValue {
-    pub user_id: String,
-    pub user_address: String,
-    pub user_owner_address: String,
-    pub subscription_fee: i64,
-    pub subscription_deadline: i64,
-    pub active: bool,
-    pub registered_at: i64,
+/// Name of the pipeline writing `table`.
+pub fn pipeline_of(table: &str) -> Option<&'static str> {
+    [
+        (ArchivePipeline::NAME, ArchivePipeline::TABLES),
+        (Offers::NAME, Offers::TABLES),
+        (Agents::NAME, Agents::TABLES),
+        (Users::NAME, Users::TABLES),
+        (ManualBuys::NAME, ManualBuys::TABLES),
+        (ShopPurchases::NAME, ShopPurchases::TABLES),
+    ]
+    .into_iter()
+    .find(|(_, tables)| tables.contains(&table))
+    .map(|(name, _)| name)
 }
 
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = BuyOffer)]
-pub struct BuyOfferValue {
-    pub buy_offer_id: String,
-    pub owner: String,
-    pub product: String,
-    pub price: i64,
-    pub offer_type_is_time_based: bool,
-    pub deadline: i64,
-    pub created_at: i64,
-}
+// ============== INDEXED VALUES ==============
 
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = SellOffer)]
-pub struct SellOfferValue {
-    pub buy_offer_id: String,
-    pub sell_offer_id: String,
-    pub agent_id: String,
-    pub agent_address: String,
-    pub store_link: String,
-    pub price: i64,
-    pub is_update: bool,
+/// A decoded event waiting to be projected.
+#[derive(Debug)]
+pub struct IndexedEvent {
+    pub origin: EventOrigin,
+    pub projection: Box<dyn Projection>,
 }
 
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = ManualBuy)]
-pub struct ManualBuyValue {
-    pub buy_offer_id: String,
-    pub buyer: String,
-    pub agent_id: String,
-    pub sell_offer_id: String,
-    pub store_link: String,
-    pub product_price: i64,
-    pub agent_fee: i64,
-    pub total_paid: i64,
+/// An event from a configured package, waiting to be archived.
+#[derive(Debug)]
+pub struct ArchivedEvent {
+    pub raw: RawEventValue,
+    /// Set when decoding failed under [`DecodeErrorPolicy::DeadLetter`].
+    pub failure: Option<FailedEventValue>,
 }
 
+/// Marks the end of a processed checkpoint, so checkpoint jobs run after the
+/// checkpoint's events and before the next checkpoint's.
 #[derive(Debug)]
-pub struct BuyOfferModifiedData {
-    pub buy_offer_id: String,
-    pub new_price: i64,
+pub struct CheckpointTick {
+    pub time: CheckpointTime,
+    pub jobs: Arc<[Arc<dyn CheckpointJob>]>,
 }
 
-#[derive(Insertable, Debug, FieldCount)]
-#[diesel(table_name = ShopPurchase)]
-pub struct ShopPurchaseValue {
-    pub agent_id: String,
-    pub store_link: String,
-    pub product_price: i64,
-    pub agent_fee: i64,
-    pub platform_fee: i64,
+#[derive(Debug)]
+pub enum IndexedValue {
+    Event(IndexedEvent),
+    Checkpoint(CheckpointTick),
 }
 
-// ============== UNIFIED EVENT ENUM ==============
-
-pub enum IndexedEvent {
-    Agent(AgentValue),
-    User(UserValue),
-    BuyOffer(BuyOfferValue),
-    SellOffer(SellOfferValue),
-    ManualBuy(ManualBuyValue),
-    BuyOfferDeleted(String), // just the buy_offer_id
-    BuyOfferModified(BuyOfferModifiedData),
-    ShopPurchase(ShopPurchaseValue),
+// The framework sizes concurrent commits by the columns per value. The widest
+// an event stages is a registration with its history row.
+impl FieldCount for IndexedEvent {
+    const FIELD_COUNT: usize = AgentValue::FIELD_COUNT + AgentHistoryValue::FIELD_COUNT;
 }
 
-// ============== UNIFIED EVENT PIPELINE ==============
-
-pub struct EventPipeline {
-    package_id: String,
+impl FieldCount for ArchivedEvent {
+    const FIELD_COUNT: usize = RawEventValue::FIELD_COUNT + FailedEventValue::FIELD_COUNT;
 }
 
-impl Processor for EventPipeline {
-    const NAME: &'static str = "events";
+// ============== EVENT SOURCE ==============
+
+/// Picks the events of the configured PriceLess packages out of a checkpoint
+/// and decodes them. Shared by every pipeline.
+struct EventSource {
+    name: &'static str,
+    packages: PackageConfig,
+    registry: EventRegistry,
+    decode_errors: DecodeErrorPolicy,
+    /// Packages outside `packages` that emitted events matching a registered
+    /// handler, reported once each.
+    unknown_packages: Mutex<HashSet<ObjectID>>,
+    /// Events skipped for coming from an unknown package. Only reported in
+    /// the warning logged for each new package, not exported as a metric.
+    unknown_package_events: AtomicU64,
+}
 
-    type Value = IndexedEvent;
+impl EventSource {
+    fn new(name: &'static str, packages: PackageConfig, registry: EventRegistry) -> Self {
+        Self {
+            name,
+            packages,
+            registry,
+            decode_errors: DecodeErrorPolicy::default(),
+            unknown_packages: Mutex::new(HashSet::new()),
+            unknown_package_events: AtomicU64::new(0),
+        }
+    }
 
-    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+    /// The checkpoint's place in chain time and its events from configured
+    /// packages, in chain order.
+    fn events<'c>(&self, checkpoint: &'c CheckpointData) -> Result<(CheckpointTime, Vec<(&'c Event, EventOrigin)>)> {
         let checkpoint_seq = checkpoint.checkpoint_summary.sequence_number;
         let timestamp_ms: u64 = checkpoint.checkpoint_summary.timestamp_ms.into();
-        let registered_at = i64::try_from(timestamp_ms)
+        let checkpoint_timestamp_ms = i64::try_from(timestamp_ms)
             .context("Timestamp too large to convert to i64")?;
+        let checkpoint_number = i64::try_from(checkpoint_seq)
+            .context("Checkpoint sequence number too large to convert to i64")?;
 
-        let mut values = Vec::new();
+        let mut events = Vec::new();
 
         for tx in &checkpoint.transactions {
             let tx_digest = tx.transaction.digest().to_string();
 
-            if let Some(events) = &tx.events {
-                for event in &events.data {
-                    if let Some(indexed_event) = self.process_event(event, &tx_digest, registered_at)? {
-                        values.push(indexed_event);
+            if let Some(tx_events) = &tx.events {
+                for (event_seq, event) in tx_events.data.iter().enumerate() {
+                    let origin = EventOrigin {
+                        tx_digest: tx_digest.clone(),
+                        event_seq: event_seq as i64,
+                        checkpoint: checkpoint_number,
+                        checkpoint_timestamp_ms,
+                    };
+
+                    let package_id = ObjectID::from(event.type_.address);
+
+                    if self.packages.version_at(&package_id, checkpoint_seq).is_none() {
+                        if self.registry.handles(&event.type_) {
+                            self.report_unknown_package(package_id, event, &origin);
+                        }
+                        continue;
                     }
+
+                    events.push((event, origin));
                 }
             }
         }
 
+        let time = CheckpointTime {
+            checkpoint: checkpoint_number,
+            timestamp_ms: checkpoint_timestamp_ms,
+        };
+
+        Ok((time, events))
+    }
+
+    /// Decode an event for projection. Events without a handler, and events
+    /// that fail to decode unless the policy is [`DecodeErrorPolicy::Fail`],
+    /// are left out; the archive pipeline records the failures.
+    fn project(&self, event: &Event, origin: EventOrigin) -> Result<Option<IndexedEvent>> {
+        let Some(decoded) = self.registry.decode(&event.type_, &event.contents, &origin) else {
+            return Ok(None);
+        };
+
+        match decoded {
+            Ok(projection) => Ok(Some(IndexedEvent { origin, projection })),
+            Err(e) => {
+                self.decode_failed(event, &origin, e)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Log a decoding failure, returning it as an error when the policy is to
+    /// stop and as a value otherwise.
+    fn decode_failed(&self, event: &Event, origin: &EventOrigin, e: anyhow::Error) -> Result<anyhow::Error> {
+        error!(
+            "[{}] Failed to parse {} event in tx {}: {:#}",
+            self.name, event.type_, origin.tx_digest, e
+        );
+
+        match self.decode_errors {
+            DecodeErrorPolicy::Fail => Err(e),
+            DecodeErrorPolicy::Skip | DecodeErrorPolicy::DeadLetter => Ok(e),
+        }
+    }
+
+    fn report_unknown_package(&self, package_id: ObjectID, event: &Event, origin: &EventOrigin) {
+        let total = self.unknown_package_events.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let first_seen = self
+            .unknown_packages
+            .lock()
+            .map(|mut seen| seen.insert(package_id))
+            .unwrap_or(false);
+
+        if first_seen {
+            warn!(
+                "[{}] Skipping {} in tx {}: package {} is not a configured PriceLess version \
+                 (add it to PACKAGE_VERSIONS if it is an upgrade). {} such events so far",
+                self.name, event.type_, origin.tx_digest, package_id, total
+            );
+        }
+    }
+}
+
+// ============== SEQUENTIAL EVENT PIPELINES ==============
+
+/// Projects the events writing to `T`'s tables in chain order, one batch per
+/// transaction, running the checkpoint jobs for those tables as it goes.
+pub struct EventPipeline<T: EventTables> {
+    source: EventSource,
+    jobs: Arc<[Arc<dyn CheckpointJob>]>,
+    _tables: PhantomData<T>,
+}
+
+impl<T: EventTables> EventPipeline<T> {
+    /// A pipeline for the handlers and jobs in `registry` that write to `T`'s tables.
+    pub fn new(packages: PackageConfig, registry: &EventRegistry) -> Self {
+        let registry = registry.writing_any(T::TABLES);
+        let jobs = registry.jobs().cloned().collect();
+
+        Self {
+            source: EventSource::new(T::NAME, packages, registry),
+            jobs,
+            _tables: PhantomData,
+        }
+    }
+
+    /// Set what happens to events that fail to decode. Defaults to
+    /// [`DecodeErrorPolicy::Fail`].
+    pub fn with_decode_error_policy(mut self, policy: DecodeErrorPolicy) -> Self {
+        self.source.decode_errors = policy;
+        self
+    }
+
+    /// Whether no registered handler writes to this pipeline's tables.
+    pub fn is_empty(&self) -> bool {
+        self.source.registry.is_empty()
+    }
+}
+
+impl<T: EventTables> Processor for EventPipeline<T> {
+    const NAME: &'static str = T::NAME;
+
+    type Value = IndexedValue;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let (time, events) = self.source.events(checkpoint)?;
+
+        let mut values = Vec::new();
+
+        for (event, origin) in events {
+            if let Some(indexed_event) = self.source.project(event, origin)? {
+                values.push(IndexedValue::Event(indexed_event));
+            }
+        }
+
         if !values.is_empty() {
             info!(
-                "Checkpoint {}: Found {} events to index",
-                checkpoint_seq,
+                "[{}] Checkpoint {}: Found {} events to index",
+                T::NAME,
+                time.checkpoint,
                 values.len()
             );
         }
 
+        if !self.jobs.is_empty() {
+            values.push(IndexedValue::Checkpoint(CheckpointTick {
+                time,
+                jobs: self.jobs.clone(),
+            }));
+        }
+
         Ok(values)
     }
 }
 
 #[async_trait]
-impl Handler for EventPipeline {
+impl<T: EventTables> SequentialHandler for EventPipeline<T> {
     type Store = Db;
     type Batch = Vec<Self::Value>;
 
@@ -249,428 +345,235 @@ impl Handler for EventPipeline {
             return Ok(0);
         }
 
-        let mut total_count = 0;
+        // Rows that are only ever inserted go first, a few statements per
+        // table. Everything else is then applied in chain order.
+        let mut batch = InsertBatch::default();
 
-        for event in values {
-            match event {
-                IndexedEvent::Agent(agent_value) => {
-                    let count = diesel::insert_into(Agent::table)
-                        .values(agent_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::User(user_value) => {
-                    let count = diesel::insert_into(User::table)
-                        .values(user_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::BuyOffer(buy_offer_value) => {
-                    let count = diesel::insert_into(BuyOffer::table)
-                        .values(buy_offer_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::SellOffer(sell_offer_value) => {
-                    let count = diesel::insert_into(SellOffer::table)
-                        .values(sell_offer_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::ManualBuy(manual_buy_value) => {
-                    let count = diesel::insert_into(ManualBuy::table)
-                        .values(manual_buy_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::BuyOfferDeleted(buy_offer_id) => {
-                    // Delete from SellOffer table first (foreign key constraint)
-                    diesel::delete(SellOffer::table.filter(SellOffer::buy_offer_id.eq(buy_offer_id.clone())))
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-
-                    // Delete from BuyOffer table
-                    let count = diesel::delete(BuyOffer::table.filter(BuyOffer::buy_offer_id.eq(buy_offer_id)))
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
-                }
-                IndexedEvent::BuyOfferModified(modified_data) => {
-                    // Update price in BuyOffer table
-                    let count = diesel::update(BuyOffer::table.filter(BuyOffer::buy_offer_id.eq(modified_data.buy_offer_id.clone())))
-                        .set(BuyOffer::price.eq(modified_data.new_price))
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
+        for value in values {
+            if let IndexedValue::Event(event) = value {
+                event.projection.stage(&mut batch);
+            }
+        }
+
+        batch.retain_tables(T::TABLES);
+        let mut total_count = batch.write(conn).await?;
+
+        // Consecutive ticks are handed to the jobs together, so a stretch of
+        // checkpoints without PriceLess events costs one run per job.
+        let mut ticks: Vec<CheckpointTime> = Vec::new();
+        let mut jobs: Option<&Arc<[Arc<dyn CheckpointJob>]>> = None;
+
+        for value in values {
+            match value {
+                IndexedValue::Event(event) => {
+                    if let Some(jobs) = jobs {
+                        total_count += run_jobs(jobs, &ticks, conn).await?;
+                        ticks.clear();
+                    }
+                    total_count += event.projection.commit(conn).await?;
                 }
-                IndexedEvent::ShopPurchase(shop_purchase_value) => {
-                    let count = diesel::insert_into(ShopPurchase::table)
-                        .values(shop_purchase_value)
-                        .on_conflict_do_nothing()
-                        .execute(conn)
-                        .await
-                        .map_err(Into::<Error>::into)?;
-                    total_count += count;
+                IndexedValue::Checkpoint(tick) => {
+                    ticks.push(tick.time);
+                    jobs = Some(&tick.jobs);
                 }
             }
         }
 
+        if let Some(jobs) = jobs {
+            total_count += run_jobs(jobs, &ticks, conn).await?;
+        }
+
         Ok(total_count)
     }
 }
 
-impl EventPipeline {
-    pub fn new(package_id: String) -> Self {
-        Self { package_id }
+/// Run every job over `ticks`, returning the number of rows affected.
+pub(crate) async fn run_jobs<'a>(
+    jobs: &[Arc<dyn CheckpointJob>],
+    ticks: &[CheckpointTime],
+    conn: &mut Connection<'a>,
+) -> Result<usize> {
+    let mut count = 0;
+
+    if ticks.is_empty() {
+        return Ok(count);
     }
 
-    fn process_event(
-        &self,
-        event: &Event,
-        tx_digest: &str,
-        _registered_at: i64,
-    ) -> Result<Option<IndexedEvent>> {
-        let event_type = event.type_.to_string();
-
-        // Extract package ID from event type
-        let event_package_id = if let Some(module_start) = event_type.find("::") {
-            &event_type[..module_start]
-        } else {
-            return Ok(None);
-        };
+    for job in jobs {
+        count += job
+            .run(ticks, conn)
+            .await
+            .with_context(|| format!("{} job failed", job.name()))?;
+    }
 
-        // Normalize package IDs by removing leading zeros after 0x
-        let normalize_package_id = |id: &str| -> String {
-            if let Some(hex_part) = id.strip_prefix("0x") {
-                format!("0x{}", hex_part.trim_start_matches('0'))
-            } else {
-                id.to_string()
-            }
-        };
+    Ok(count)
+}
 
-        let normalized_event_package = normalize_package_id(event_package_id);
-        let normalized_expected_package = normalize_package_id(&self.package_id);
+// ============== CONCURRENT APPEND PIPELINES ==============
 
-        if normalized_event_package != normalized_expected_package {
-            return Ok(None);
+/// Inserts the rows staged for `T`'s tables. Batches commit in any order and in
+/// parallel, so only `stage` is used: `commit` is left to the sequential
+/// pipelines owning the tables it updates.
+pub struct AppendPipeline<T: EventTables> {
+    source: EventSource,
+    _tables: PhantomData<T>,
+}
+
+impl<T: EventTables> AppendPipeline<T> {
+    /// A pipeline for the handlers in `registry` that write to `T`'s tables.
+    pub fn new(packages: PackageConfig, registry: &EventRegistry) -> Self {
+        Self {
+            source: EventSource::new(T::NAME, packages, registry.writing_any(T::TABLES)),
+            _tables: PhantomData,
         }
+    }
 
-        // Check if this is an AgentRegistered event
-        if event_type.contains("::AgentRegistered") || event_type.ends_with("::agent_registered") {
-            info!(
-                "Agent registration event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+    /// Set what happens to events that fail to decode. Defaults to
+    /// [`DecodeErrorPolicy::Fail`].
+    pub fn with_decode_error_policy(mut self, policy: DecodeErrorPolicy) -> Self {
+        self.source.decode_errors = policy;
+        self
+    }
 
-            match bcs::from_bytes::<AgentRegisteredEvent>(&event.contents) {
-                Ok(agent_event) => {
-                    info!(
-                        "Successfully parsed AgentRegistered - agent_id: {}, object_address: {}, owner_address: {}, stake_amount: {}, timestamp: {}, tx: {}",
-                        agent_event.agent_id, agent_event.agent_object_address, agent_event.agent_owner_address, agent_event.stake_amount, agent_event.timestamp, tx_digest
-                    );
-
-                    let stake_amount = i64::try_from(agent_event.stake_amount)
-                        .context("Stake amount too large to convert to i64")?;
-                    let registered_at = i64::try_from(agent_event.timestamp)
-                        .context("Timestamp too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::Agent(AgentValue {
-                        agent_id: agent_event.agent_id.to_string(),
-                        agent_address: agent_event.agent_object_address.to_string(),
-                        agent_owner_address: agent_event.agent_owner_address.to_string(),
-                        stake_amount,
-                        rating: 500,
-                        buys: 0,
-                        active: true,
-                        registered_at,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse AgentRegistered event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
-            }
-        }
+    /// Whether no registered handler writes to this pipeline's tables.
+    pub fn is_empty(&self) -> bool {
+        self.source.registry.is_empty()
+    }
+}
 
-        // Check if this is a UserRegistered event
-        if event_type.contains("::UserRegistered") || event_type.ends_with("::user_registered") {
-            info!(
-                "User registration event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+impl<T: EventTables> Processor for AppendPipeline<T> {
+    const NAME: &'static str = T::NAME;
 
-            match bcs::from_bytes::<UserRegisteredEvent>(&event.contents) {
-                Ok(user_event) => {
-                    info!(
-                        "Successfully parsed UserRegistered - user_id: {}, object_address: {}, owner_address: {}, subscription_fee: {}, subscription_deadline: {}, timestamp: {}, tx: {}",
-                        user_event.user_id, user_event.user_object_address, user_event.user_owner_address, user_event.subscription_fee, user_event.subscription_deadline, user_event.timestamp, tx_digest
-                    );
-
-                    let subscription_fee = i64::try_from(user_event.subscription_fee)
-                        .context("Subscription fee too large to convert to i64")?;
-                    let subscription_deadline = i64::try_from(user_event.subscription_deadline)
-                        .context("Subscription deadline too large to convert to i64")?;
-                    let registered_at = i64::try_from(user_event.timestamp)
-                        .context("Timestamp too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::User(UserValue {
-                        user_id: user_event.user_id.to_string(),
-                        user_address: user_event.user_object_address.to_string(),
-                        user_owner_address: user_event.user_owner_address.to_string(),
-                        subscription_fee,
-                        subscription_deadline,
-                        active: true,
-                        registered_at,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse UserRegistered event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
-            }
-        }
+    type Value = IndexedEvent;
 
-        // Check if this is a BuyOfferCreated event
-        if event_type.contains("::BuyOfferCreated") || event_type.ends_with("::buy_offer_created") {
-            info!(
-                "Buy offer created event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let (_, events) = self.source.events(checkpoint)?;
 
-            match bcs::from_bytes::<BuyOfferCreatedEvent>(&event.contents) {
-                Ok(buy_offer_event) => {
-                    info!(
-                        "Successfully parsed BuyOfferCreated - buy_offer_id: {}, owner: {}, product: {}, price: {}, is_time_based: {}, deadline: {}, timestamp: {}, tx: {}",
-                        buy_offer_event.buy_offer_id, buy_offer_event.owner, buy_offer_event.product, buy_offer_event.price, buy_offer_event.offer_type_is_time_based, buy_offer_event.deadline, buy_offer_event.timestamp, tx_digest
-                    );
-
-                    let price = i64::try_from(buy_offer_event.price)
-                        .context("Price too large to convert to i64")?;
-                    let deadline = i64::try_from(buy_offer_event.deadline)
-                        .context("Deadline too large to convert to i64")?;
-                    let created_at = i64::try_from(buy_offer_event.timestamp)
-                        .context("Timestamp too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::BuyOffer(BuyOfferValue {
-                        buy_offer_id: buy_offer_event.buy_offer_id.to_string(),
-                        owner: buy_offer_event.owner.to_string(),
-                        product: buy_offer_event.product,
-                        price,
-                        offer_type_is_time_based: buy_offer_event.offer_type_is_time_based,
-                        deadline,
-                        created_at,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse BuyOfferCreated event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
+        let mut values = Vec::new();
+
+        for (event, origin) in events {
+            if let Some(indexed_event) = self.source.project(event, origin)? {
+                values.push(indexed_event);
             }
         }
 
-        // Check if this is a SellOfferMade event
-        if event_type.contains("::SellOfferMade") || event_type.ends_with("::sell_offer_made") {
-            info!(
-                "Sell offer made event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+        Ok(values)
+    }
+}
 
-            match bcs::from_bytes::<SellOfferMadeEvent>(&event.contents) {
-                Ok(sell_offer_event) => {
-                    info!(
-                        "Successfully parsed SellOfferMade - buy_offer_id: {}, sell_offer_id: {}, agent_id: {}, agent_address: {}, store_link: {}, price: {}, is_update: {}, tx: {}",
-                        sell_offer_event.buy_offer_id, sell_offer_event.sell_offer_id, sell_offer_event.agent_id, sell_offer_event.agent_address, sell_offer_event.store_link, sell_offer_event.price, sell_offer_event.is_update, tx_digest
-                    );
-
-                    let price = i64::try_from(sell_offer_event.price)
-                        .context("Price too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::SellOffer(SellOfferValue {
-                        buy_offer_id: sell_offer_event.buy_offer_id.to_string(),
-                        sell_offer_id: sell_offer_event.sell_offer_id.to_string(),
-                        agent_id: sell_offer_event.agent_id.to_string(),
-                        agent_address: sell_offer_event.agent_address.to_string(),
-                        store_link: sell_offer_event.store_link,
-                        price,
-                        is_update: sell_offer_event.is_update,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse SellOfferMade event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
-            }
-        }
+#[async_trait]
+impl<T: EventTables> ConcurrentHandler for AppendPipeline<T> {
+    type Store = Db;
 
-        // Check if this is a ManualBuy event
-        if event_type.contains("::ManualBuy") || event_type.ends_with("::manual_buy") {
-            info!(
-                "Manual buy event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+    async fn commit<'a>(
+        values: &[Self::Value],
+        conn: &mut <Self::Store as Store>::Connection<'a>,
+    ) -> Result<usize> {
+        let mut batch = InsertBatch::default();
 
-            match bcs::from_bytes::<ManualBuyEvent>(&event.contents) {
-                Ok(manual_buy_event) => {
-                    info!(
-                        "Successfully parsed ManualBuy - buy_offer_id: {}, buyer: {}, agent_id: {}, sell_offer_id: {}, store_link: {}, product_price: {}, agent_fee: {}, total_paid: {}, tx: {}",
-                        manual_buy_event.buy_offer_id, manual_buy_event.buyer, manual_buy_event.agent_id, manual_buy_event.sell_offer_id, manual_buy_event.store_link, manual_buy_event.product_price, manual_buy_event.agent_fee, manual_buy_event.total_paid, tx_digest
-                    );
-
-                    let product_price = i64::try_from(manual_buy_event.product_price)
-                        .context("Product price too large to convert to i64")?;
-                    let agent_fee = i64::try_from(manual_buy_event.agent_fee)
-                        .context("Agent fee too large to convert to i64")?;
-                    let total_paid = i64::try_from(manual_buy_event.total_paid)
-                        .context("Total paid too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::ManualBuy(ManualBuyValue {
-                        buy_offer_id: manual_buy_event.buy_offer_id.to_string(),
-                        buyer: manual_buy_event.buyer.to_string(),
-                        agent_id: manual_buy_event.agent_id.to_string(),
-                        sell_offer_id: manual_buy_event.sell_offer_id.to_string(),
-                        store_link: manual_buy_event.store_link,
-                        product_price,
-                        agent_fee,
-                        total_paid,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse ManualBuy event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
-            }
+        for event in values {
+            event.projection.stage(&mut batch);
         }
 
-        // Check if this is a BuyOfferDeleted event
-        if event_type.contains("::BuyOfferDeleted") || event_type.ends_with("::buy_offer_deleted") {
-            info!(
-                "Buy offer deleted event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+        batch.retain_tables(T::TABLES);
+        batch.write(conn).await
+    }
+}
 
-            match bcs::from_bytes::<BuyOfferDeletedEvent>(&event.contents) {
-                Ok(buy_offer_deleted_event) => {
-                    info!(
-                        "Successfully parsed BuyOfferDeleted - buy_offer_id: {}, owner: {}, remaining_balance: {}, tx: {}",
-                        buy_offer_deleted_event.buy_offer_id, buy_offer_deleted_event.owner, buy_offer_deleted_event.remaining_balance, tx_digest
-                    );
+// ============== RAW EVENT ARCHIVE ==============
 
-                    return Ok(Some(IndexedEvent::BuyOfferDeleted(buy_offer_deleted_event.buy_offer_id.to_string())));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse BuyOfferDeleted event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
-            }
+/// Archives every event from a configured package in `RawEvent`, rendered as
+/// JSON when a handler knows its type, and records events that fail to decode
+/// in `FailedEvent` under [`DecodeErrorPolicy::DeadLetter`].
+pub struct ArchivePipeline {
+    source: EventSource,
+}
+
+impl ArchivePipeline {
+    pub const TABLES: &'static [&'static str] = &["RawEvent", "FailedEvent"];
+
+    pub fn new(packages: PackageConfig, registry: &EventRegistry) -> Self {
+        Self {
+            source: EventSource::new(Self::NAME, packages, registry.clone()),
         }
+    }
 
-        // Check if this is a BuyOfferModified event
-        if event_type.contains("::BuyOfferModified") || event_type.ends_with("::buy_offer_modified") {
-            info!(
-                "Buy offer modified event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+    /// Set what happens to events that fail to decode. Defaults to
+    /// [`DecodeErrorPolicy::Fail`].
+    pub fn with_decode_error_policy(mut self, policy: DecodeErrorPolicy) -> Self {
+        self.source.decode_errors = policy;
+        self
+    }
 
-            match bcs::from_bytes::<BuyOfferModifiedEvent>(&event.contents) {
-                Ok(buy_offer_modified_event) => {
-                    info!(
-                        "Successfully parsed BuyOfferModified - buy_offer_id: {}, owner: {}, old_price: {}, new_price: {}, price_reduction: {}, tx: {}",
-                        buy_offer_modified_event.buy_offer_id, buy_offer_modified_event.owner, buy_offer_modified_event.old_price, buy_offer_modified_event.new_price, buy_offer_modified_event.price_reduction, tx_digest
-                    );
+    fn archive(&self, event: &Event, origin: EventOrigin) -> Result<ArchivedEvent> {
+        let registry = &self.source.registry;
 
-                    let new_price = i64::try_from(buy_offer_modified_event.new_price)
-                        .context("New price too large to convert to i64")?;
+        let decoded = registry
+            .decode(&event.type_, &event.contents, &origin)
+            .transpose()
+            .and_then(|_| registry.render(&event.type_, &event.contents).transpose());
 
-                    return Ok(Some(IndexedEvent::BuyOfferModified(BuyOfferModifiedData {
-                        buy_offer_id: buy_offer_modified_event.buy_offer_id.to_string(),
-                        new_price,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse BuyOfferModified event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
+        let (json, error) = match decoded {
+            Ok(json) => (json, None),
+            Err(e) => {
+                let e = self.source.decode_failed(event, &origin, e)?;
+                let dead_letter = self.source.decode_errors == DecodeErrorPolicy::DeadLetter;
+                (None, dead_letter.then(|| format!("{e:#}")))
             }
-        }
+        };
 
-        // Check if this is a ShopPurchase event
-        if event_type.contains("::ShopPurchase") || event_type.ends_with("::shop_purchase") {
-            info!(
-                "Shop purchase event detected: {} in tx: {}",
-                event_type, tx_digest
-            );
+        let raw = RawEventValue {
+            package_id: ObjectID::from(event.type_.address).to_string(),
+            module: event.type_.module.to_string(),
+            struct_name: event.type_.name.to_string(),
+            event_type: event.type_.to_string(),
+            tx_digest: origin.tx_digest,
+            event_seq: origin.event_seq,
+            sender: event.sender.to_string(),
+            checkpoint: origin.checkpoint,
+            checkpoint_timestamp_ms: origin.checkpoint_timestamp_ms,
+            bcs: event.contents.clone(),
+            json,
+        };
 
-            match bcs::from_bytes::<ShopPurchaseEvent>(&event.contents) {
-                Ok(shop_purchase_event) => {
-                    info!(
-                        "Successfully parsed ShopPurchase - agent_id: {}, store_link: {}, product_price: {}, agent_fee: {}, platform_fee: {}, tx: {}",
-                        shop_purchase_event.agent_id, shop_purchase_event.store_link, shop_purchase_event.product_price, shop_purchase_event.agent_fee, shop_purchase_event.platform_fee, tx_digest
-                    );
-
-                    let product_price = i64::try_from(shop_purchase_event.product_price)
-                        .context("Product price too large to convert to i64")?;
-                    let agent_fee = i64::try_from(shop_purchase_event.agent_fee)
-                        .context("Agent fee too large to convert to i64")?;
-                    let platform_fee = i64::try_from(shop_purchase_event.platform_fee)
-                        .context("Platform fee too large to convert to i64")?;
-
-                    return Ok(Some(IndexedEvent::ShopPurchase(ShopPurchaseValue {
-                        agent_id: shop_purchase_event.agent_id.to_string(),
-                        store_link: shop_purchase_event.store_link,
-                        product_price,
-                        agent_fee,
-                        platform_fee,
-                    })));
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to parse ShopPurchase event in tx {}: {}",
-                        tx_digest, e
-                    );
-                    return Err(e.into());
-                }
+        let failure = error.map(|error| FailedEventValue::new(&raw, error));
+
+        Ok(ArchivedEvent { raw, failure })
+    }
+}
+
+impl Processor for ArchivePipeline {
+    const NAME: &'static str = "raw_events";
+
+    type Value = ArchivedEvent;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let (_, events) = self.source.events(checkpoint)?;
+
+        events
+            .into_iter()
+            .map(|(event, origin)| self.archive(event, origin))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ConcurrentHandler for ArchivePipeline {
+    type Store = Db;
+
+    async fn commit<'a>(
+        values: &[Self::Value],
+        conn: &mut <Self::Store as Store>::Connection<'a>,
+    ) -> Result<usize> {
+        let mut batch = InsertBatch::default();
+
+        for event in values {
+            batch.raw_events.push(Cow::Borrowed(&event.raw));
+            if let Some(failure) = &event.failure {
+                batch.failed_events.push(Cow::Borrowed(failure));
             }
         }
 
-        Ok(None)
+        batch.write(conn).await
     }
 }
@@ -0,0 +1,73 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use diesel::sql_types::{Array, BigInt};
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::Connection;
+use crate::jobs::{CheckpointJob, CheckpointTime};
+
+/// Expires open time-based buy offers whose deadline has passed and closes
+/// their sell offers. On chain a manual buy is only accepted while
+/// `timestamp < deadline`, after which the offer waits for a service buy.
+///
+/// `closed_at` is the first checkpoint whose timestamp reached the deadline,
+/// so it does not depend on how checkpoints were grouped into runs. Offers
+/// created after the run are left for a later one.
+#[derive(Debug, Default)]
+pub struct BuyOfferExpiryJob;
+
+#[async_trait]
+impl CheckpointJob for BuyOfferExpiryJob {
+    fn name(&self) -> &'static str {
+        "BuyOfferExpiry"
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &["BuyOffer", "SellOffer"]
+    }
+
+    async fn run<'a>(&self, checkpoints: &[CheckpointTime], conn: &mut Connection<'a>) -> Result<usize> {
+        let Some(latest) = checkpoints.last() else {
+            return Ok(0);
+        };
+
+        let (sequence_numbers, timestamps): (Vec<i64>, Vec<i64>) = checkpoints
+            .iter()
+            .map(|c| (c.checkpoint, c.timestamp_ms))
+            .unzip();
+
+        diesel::sql_query(
+            r#"
+            WITH expired AS (
+                UPDATE "BuyOffer" b SET
+                    status = 'expired',
+                    closed_at = (
+                        SELECT MIN(c.checkpoint)
+                        FROM UNNEST($1::BIGINT[], $2::BIGINT[]) AS c(checkpoint, timestamp_ms)
+                        WHERE c.timestamp_ms >= b.deadline
+                            AND (b.checkpoint IS NULL OR c.checkpoint >= b.checkpoint)
+                    )
+                WHERE b.status = 'open'
+                    AND b.offer_type_is_time_based
+                    AND b.deadline <= $3
+                    AND (b.checkpoint IS NULL OR b.checkpoint <= $4)
+                RETURNING b.buy_offer_id, b.closed_at
+            )
+            UPDATE "SellOffer" s SET
+                status = 'closed',
+                closed_at = expired.closed_at
+            FROM expired
+            WHERE s.buy_offer_id = expired.buy_offer_id
+                AND s.status = 'open'
+            "#,
+        )
+        .bind::<Array<BigInt>, _>(sequence_numbers)
+        .bind::<Array<BigInt>, _>(timestamps)
+        .bind::<BigInt, _>(latest.timestamp_ms)
+        .bind::<BigInt, _>(latest.checkpoint)
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use std::fmt;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::Connection;
+
+pub mod buy_offer_expiry;
+pub mod user_expiry;
+
+/// A processed checkpoint's position in chain time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointTime {
+    pub checkpoint: i64,
+    pub timestamp_ms: i64,
+}
+
+/// Work driven by chain time rather than by an event, such as expiring
+/// subscriptions once their deadline passes.
+///
+/// Jobs run in the same transaction as the events they are ordered with, and
+/// only ever compare against checkpoint timestamps, so replaying a range
+/// produces the same rows as indexing it the first time.
+///
+/// Rows inserted by events later in the batch may already exist when a job
+/// runs, so jobs must ignore rows indexed after the checkpoints they run as of.
+#[async_trait]
+pub trait CheckpointJob: fmt::Debug + Send + Sync + 'static {
+    fn name(&self) -> &'static str;
+
+    /// Tables the job writes to, by their name in `schema.rs`.
+    fn tables(&self) -> &'static [&'static str];
+
+    /// Apply the job as of each of `checkpoints`, consecutive processed
+    /// checkpoints (oldest first) with no events committed between them.
+    /// Returns the number of rows affected.
+    async fn run<'a>(&self, checkpoints: &[CheckpointTime], conn: &mut Connection<'a>) -> Result<usize>;
+}
@@ -0,0 +1,44 @@
+use anyhow::Error;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::Connection;
+use crate::jobs::{CheckpointJob, CheckpointTime};
+use crate::schema::User;
+
+/// Deactivates users whose subscription deadline has passed. On chain a
+/// subscription is valid while `timestamp < subscription_deadline`.
+#[derive(Debug, Default)]
+pub struct UserExpiryJob;
+
+#[async_trait]
+impl CheckpointJob for UserExpiryJob {
+    fn name(&self) -> &'static str {
+        "UserExpiry"
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &["User"]
+    }
+
+    async fn run<'a>(&self, checkpoints: &[CheckpointTime], conn: &mut Connection<'a>) -> Result<usize> {
+        // Expiry only depends on the latest timestamp in the run.
+        let Some(latest) = checkpoints.last() else {
+            return Ok(0);
+        };
+
+        diesel::update(
+            User::table
+                .filter(User::active.eq(true))
+                .filter(User::subscription_deadline.le(BigDecimal::from(latest.timestamp_ms)))
+                .filter(User::checkpoint.is_null().or(User::checkpoint.le(latest.checkpoint))),
+        )
+        .set(User::active.eq(false))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)
+    }
+}
@@ -1,7 +1,18 @@
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
 
+pub mod events;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod handlers;
+pub mod jobs;
+pub mod models;
+pub mod orphans;
+pub mod queries;
+pub mod rebuild;
+pub mod retry;
 pub mod config;
 pub mod schema;
+pub mod server;
+pub mod status;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
@@ -0,0 +1,147 @@
+//! Rows of the projection tables as read back from the database.
+//!
+//! Amounts serialize as decimal strings, so `u64` values survive JSON clients
+//! that parse numbers as doubles. With the `graphql` feature the rows are also
+//! GraphQL objects; their relations are resolved in `crate::graphql`.
+
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::schema;
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::AgentWithStats)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Agent {
+    pub id: i32,
+    pub agent_id: String,
+    pub agent_address: String,
+    pub agent_owner_address: String,
+    pub stake_amount: BigDecimal,
+    pub rating: BigDecimal,
+    pub active: bool,
+    pub registered_at: BigDecimal,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+    /// Statistics over the agent's `ShopPurchase` rows, computed when read.
+    pub buys: i64,
+    pub total_fees_earned: BigDecimal,
+    pub total_volume: BigDecimal,
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::BuyOffer)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct BuyOffer {
+    pub id: i32,
+    pub buy_offer_id: String,
+    pub owner: String,
+    pub product: String,
+    pub price: BigDecimal,
+    pub offer_type_is_time_based: bool,
+    pub deadline: BigDecimal,
+    pub created_at: BigDecimal,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+    /// One of [`crate::status::BuyOfferStatus`].
+    pub status: String,
+    /// Checkpoint at which the offer was cancelled, bought or expired.
+    pub closed_at: Option<i64>,
+    pub remaining_balance: Option<BigDecimal>,
+    pub accepted_sell_offer_id: Option<String>,
+    pub total_paid: Option<BigDecimal>,
+    /// Checkpoint at which the offer was bought.
+    pub filled_at: Option<i64>,
+    /// Checkpoint of the latest price change.
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::SellOffer)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SellOffer {
+    pub id: i32,
+    pub buy_offer_id: String,
+    pub sell_offer_id: String,
+    pub agent_id: String,
+    pub agent_address: String,
+    pub store_link: String,
+    pub price: BigDecimal,
+    pub is_update: bool,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+    /// One of [`crate::status::SellOfferStatus`].
+    pub status: String,
+    /// Checkpoint at which the offer was accepted, rejected or closed.
+    pub closed_at: Option<i64>,
+    /// Checkpoint of the latest revision.
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::ManualBuy)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ManualBuy {
+    pub id: i32,
+    pub buy_offer_id: String,
+    pub buyer: String,
+    pub agent_id: String,
+    pub sell_offer_id: String,
+    pub store_link: String,
+    pub product_price: BigDecimal,
+    pub agent_fee: BigDecimal,
+    pub total_paid: BigDecimal,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+}
+
+#[derive(Queryable, QueryableByName, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::ShopPurchase)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ShopPurchase {
+    pub id: i32,
+    pub agent_id: String,
+    pub store_link: String,
+    pub product_price: BigDecimal,
+    pub agent_fee: BigDecimal,
+    pub platform_fee: BigDecimal,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
+#[diesel(table_name = schema::User)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct User {
+    pub id: i32,
+    pub user_id: String,
+    pub user_address: String,
+    pub user_owner_address: String,
+    pub subscription_fee: BigDecimal,
+    pub subscription_deadline: BigDecimal,
+    pub active: bool,
+    pub registered_at: BigDecimal,
+    pub tx_digest: Option<String>,
+    pub event_seq: Option<i64>,
+    pub checkpoint: Option<i64>,
+    pub checkpoint_timestamp_ms: Option<i64>,
+    pub subscription_cancelled_at: Option<BigDecimal>,
+    pub updated_at: Option<i64>,
+}
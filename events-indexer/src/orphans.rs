@@ -0,0 +1,196 @@
+use anyhow::{bail, Error};
+use diesel::sql_types::{BigInt, Nullable, Text};
+use diesel::QueryableByName;
+use diesel_async::RunQueryDsl;
+use sui_indexer_alt_framework::postgres::store::Store;
+use sui_indexer_alt_framework::postgres::Db;
+use sui_indexer_alt_framework::Result;
+
+use crate::config::PackageConfig;
+use crate::events::buy_offer_created::BuyOfferCreatedHandler;
+use crate::events::sell_offer_made::SellOfferMadeHandler;
+use crate::events::{EventHandler, EventRegistry};
+
+/// A reference between two projection tables, by their names in `schema.rs`.
+#[derive(Debug)]
+pub struct Reference {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub referenced_table: &'static str,
+    pub referenced_column: &'static str,
+}
+
+/// Every reference between projection tables. Only `SellOffer.buy_offer_id`
+/// is enforced by a foreign key, for rows indexed after it was added; the
+/// others point into tables written by other pipelines, whose rows may never
+/// be indexed, and are only checked by [`find_orphans`].
+pub const REFERENCES: [Reference; 6] = [
+    Reference {
+        table: "BuyOffer",
+        column: "owner",
+        referenced_table: "User",
+        referenced_column: "user_owner_address",
+    },
+    Reference {
+        table: "SellOffer",
+        column: "buy_offer_id",
+        referenced_table: "BuyOffer",
+        referenced_column: "buy_offer_id",
+    },
+    Reference {
+        table: "SellOffer",
+        column: "agent_id",
+        referenced_table: "Agent",
+        referenced_column: "agent_id",
+    },
+    Reference {
+        table: "ManualBuy",
+        column: "buy_offer_id",
+        referenced_table: "BuyOffer",
+        referenced_column: "buy_offer_id",
+    },
+    Reference {
+        table: "ManualBuy",
+        column: "sell_offer_id",
+        referenced_table: "SellOffer",
+        referenced_column: "sell_offer_id",
+    },
+    Reference {
+        table: "ManualBuy",
+        column: "agent_id",
+        referenced_table: "Agent",
+        referenced_column: "agent_id",
+    },
+];
+
+/// A row whose referent was never indexed, e.g. because indexing started
+/// after the referenced event.
+#[derive(Debug)]
+pub struct Orphan {
+    pub reference: &'static Reference,
+    pub id: i64,
+    /// The referencing column's value.
+    pub value: String,
+    pub checkpoint: Option<i64>,
+}
+
+#[derive(QueryableByName)]
+struct OrphanRow {
+    #[diesel(sql_type = BigInt)]
+    id: i64,
+    #[diesel(sql_type = Text)]
+    value: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    checkpoint: Option<i64>,
+}
+
+/// Rows of every table in [`REFERENCES`] whose referent is missing, in table
+/// order and then chain order.
+pub async fn find_orphans(db: &Db) -> Result<Vec<Orphan>> {
+    let mut conn = db.connect().await?;
+    let mut orphans = vec![];
+
+    for reference in &REFERENCES {
+        let Reference {
+            table,
+            column,
+            referenced_table,
+            referenced_column,
+        } = reference;
+
+        let rows: Vec<OrphanRow> = diesel::sql_query(format!(
+            r#"
+            SELECT t.id::BIGINT AS id, t.{column} AS value, t.checkpoint
+            FROM "{table}" t
+            WHERE NOT EXISTS (
+                SELECT 1 FROM "{referenced_table}" r WHERE r.{referenced_column} = t.{column}
+            )
+            ORDER BY t.checkpoint NULLS FIRST, t.id
+            "#
+        ))
+        .load(&mut conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+        orphans.extend(rows.into_iter().map(|row| Orphan {
+            reference,
+            id: row.id,
+            value: row.value,
+            checkpoint: row.checkpoint,
+        }));
+    }
+
+    Ok(orphans)
+}
+
+/// Refuse configurations under which sell offers are knowingly dropped for
+/// want of their buy offer: indexing `SellOfferMade` without `BuyOfferCreated`,
+/// or starting after the package's known first checkpoint, past the creation
+/// of earlier buy offers. When the first checkpoint is unknown (`0`) indexing
+/// may start anywhere; sell offers on buy offers created before it are skipped
+/// with a warning.
+pub fn check_enforced_references(
+    registry: &EventRegistry,
+    packages: &PackageConfig,
+    first_checkpoint: Option<u64>,
+) -> Result<()> {
+    let indexes = |name: &str| registry.names().any(|n| n == name);
+
+    if !indexes(SellOfferMadeHandler::NAME) {
+        return Ok(());
+    }
+
+    if !indexes(BuyOfferCreatedHandler::NAME) {
+        bail!(
+            "SellOfferMade is indexed without BuyOfferCreated, but sell offers must reference an \
+             indexed buy offer; enable BuyOfferCreated or disable SellOfferMade"
+        );
+    }
+
+    let known = packages.first_checkpoint();
+
+    match first_checkpoint {
+        Some(first) if known > 0 && first > known => bail!(
+            "Indexing would start at checkpoint {first}, after the package's first checkpoint {known}, \
+             so sell offers on earlier buy offers could never be written; start from it or \
+             disable SellOfferMade"
+        ),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE: &str = "0xabc";
+
+    fn check(versions: &str, first_checkpoint: Option<u64>) -> Result<()> {
+        let packages = PackageConfig::parse(versions).unwrap();
+        check_enforced_references(&EventRegistry::priceless(), &packages, first_checkpoint)
+    }
+
+    #[test]
+    fn starting_past_a_known_first_checkpoint_is_refused() {
+        let versions = format!("{PACKAGE}@500");
+
+        assert!(check(&versions, Some(500)).is_ok());
+        assert!(check(&versions, Some(400)).is_ok());
+        assert!(check(&versions, Some(501)).is_err());
+    }
+
+    #[test]
+    fn any_start_is_allowed_when_the_first_checkpoint_is_unknown() {
+        assert!(check(PACKAGE, Some(1_000_000)).is_ok());
+        assert!(check(PACKAGE, None).is_ok());
+    }
+
+    #[test]
+    fn sell_offers_need_buy_offers_indexed() {
+        let mut registry = EventRegistry::priceless();
+        registry.disable("BuyOfferCreated").unwrap();
+
+        let packages = PackageConfig::parse(PACKAGE).unwrap();
+        assert!(check_enforced_references(&registry, &packages, None).is_err());
+    }
+}
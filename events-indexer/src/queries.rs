@@ -0,0 +1,548 @@
+//! Typed reads over the projection tables.
+//!
+//! Lists are paginated by key rather than by offset, so rows indexed while a
+//! client pages through a list neither repeat nor go missing. The key of the
+//! last row returned (its `id`, unless the list says otherwise) is the `after`
+//! of the next page.
+//!
+//! `ManualBuy` and `ShopPurchase` are written by concurrent pipelines, which
+//! commit checkpoints out of order, so their ids follow commit order and a
+//! row may appear after later ones. Their lists are keyed by checkpoint
+//! instead, and stop at the pipeline's watermark, below which every row is
+//! already written.
+
+use anyhow::Error;
+use bigdecimal::BigDecimal;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Deserialize;
+use sui_indexer_alt_framework::Result;
+
+use crate::handlers::{EventTables, ManualBuys, ShopPurchases};
+use crate::models::{Agent, BuyOffer, ManualBuy, SellOffer, ShopPurchase, User};
+use crate::schema;
+use crate::status::BuyOfferStatus;
+
+/// Rows returned when a page does not say.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// One page of a list: at most `limit` rows following the row keyed `after`,
+/// or from the start when `after` is `None`.
+#[derive(Debug, Clone)]
+pub struct Page<K> {
+    pub after: Option<K>,
+    pub limit: i64,
+}
+
+impl<K> Default for Page<K> {
+    fn default() -> Self {
+        Self {
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl<K> Page<K> {
+    pub fn first(limit: i64) -> Self {
+        Self { after: None, limit }
+    }
+
+    pub fn after(key: K, limit: i64) -> Self {
+        Self {
+            after: Some(key),
+            limit,
+        }
+    }
+}
+
+/// Conditions on buy offers; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct BuyOfferFilter {
+    pub product: Option<String>,
+    pub owner: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Conditions on sell offers; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct SellOfferFilter {
+    pub buy_offer_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Conditions on agents; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct AgentFilter {
+    pub owner_address: Option<String>,
+    pub active: Option<bool>,
+}
+
+/// Conditions on users; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct UserFilter {
+    pub active: Option<bool>,
+}
+
+/// Conditions on manual buys; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct ManualBuyFilter {
+    pub buy_offer_id: Option<String>,
+    pub sell_offer_id: Option<String>,
+    pub agent_id: Option<String>,
+    pub buyer: Option<String>,
+}
+
+/// Conditions on shop purchases; unset fields match every row.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::InputObject))]
+pub struct ShopPurchaseFilter {
+    pub agent_id: Option<String>,
+    pub store_link: Option<String>,
+}
+
+impl SellOffer {
+    /// Key of this row in [`sell_offers_for_buy_offer`]. Other lists of sell
+    /// offers are keyed by `id`.
+    pub fn price_key(&self) -> (BigDecimal, i32) {
+        (self.price.clone(), self.id)
+    }
+}
+
+impl ManualBuy {
+    /// Key of this row in [`manual_buys`]: its checkpoint, `-1` for rows
+    /// indexed before checkpoints were recorded, then its id.
+    pub fn checkpoint_key(&self) -> (i64, i32) {
+        (self.checkpoint.unwrap_or(-1), self.id)
+    }
+}
+
+impl ShopPurchase {
+    /// Key of this row in [`shop_purchases`] and [`purchases_by_agent`]: its
+    /// checkpoint, `-1` for rows indexed before checkpoints were recorded, then
+    /// its id.
+    pub fn checkpoint_key(&self) -> (i64, i32) {
+        (self.checkpoint.unwrap_or(-1), self.id)
+    }
+}
+
+/// Buy offers matching `filter`, unordered, for callers adding their own
+/// order and bounds.
+pub fn buy_offers_matching(filter: &BuyOfferFilter) -> schema::BuyOffer::BoxedQuery<'_, Pg> {
+    use schema::BuyOffer::dsl;
+
+    let mut query = dsl::BuyOffer.into_boxed();
+
+    if let Some(product) = &filter.product {
+        query = query.filter(dsl::product.eq(product));
+    }
+    if let Some(owner) = &filter.owner {
+        query = query.filter(dsl::owner.eq(owner));
+    }
+    if let Some(status) = &filter.status {
+        query = query.filter(dsl::status.eq(status));
+    }
+
+    query
+}
+
+/// Sell offers matching `filter`, unordered.
+pub fn sell_offers_matching(filter: &SellOfferFilter) -> schema::SellOffer::BoxedQuery<'_, Pg> {
+    use schema::SellOffer::dsl;
+
+    let mut query = dsl::SellOffer.into_boxed();
+
+    if let Some(buy_offer_id) = &filter.buy_offer_id {
+        query = query.filter(dsl::buy_offer_id.eq(buy_offer_id));
+    }
+    if let Some(agent_id) = &filter.agent_id {
+        query = query.filter(dsl::agent_id.eq(agent_id));
+    }
+    if let Some(status) = &filter.status {
+        query = query.filter(dsl::status.eq(status));
+    }
+
+    query
+}
+
+/// Agents matching `filter`, unordered.
+pub fn agents_matching(filter: &AgentFilter) -> schema::AgentWithStats::BoxedQuery<'_, Pg> {
+    use schema::AgentWithStats::dsl;
+
+    let mut query = dsl::AgentWithStats.into_boxed();
+
+    if let Some(owner_address) = &filter.owner_address {
+        query = query.filter(dsl::agent_owner_address.eq(owner_address));
+    }
+    if let Some(active) = filter.active {
+        query = query.filter(dsl::active.eq(active));
+    }
+
+    query
+}
+
+/// Users matching `filter`, unordered.
+pub fn users_matching(filter: &UserFilter) -> schema::User::BoxedQuery<'_, Pg> {
+    use schema::User::dsl;
+
+    let mut query = dsl::User.into_boxed();
+
+    if let Some(active) = filter.active {
+        query = query.filter(dsl::active.eq(active));
+    }
+
+    query
+}
+
+/// Manual buys matching `filter`, unordered.
+pub fn manual_buys_matching(filter: &ManualBuyFilter) -> schema::ManualBuy::BoxedQuery<'_, Pg> {
+    use schema::ManualBuy::dsl;
+
+    let mut query = dsl::ManualBuy.into_boxed();
+
+    if let Some(buy_offer_id) = &filter.buy_offer_id {
+        query = query.filter(dsl::buy_offer_id.eq(buy_offer_id));
+    }
+    if let Some(sell_offer_id) = &filter.sell_offer_id {
+        query = query.filter(dsl::sell_offer_id.eq(sell_offer_id));
+    }
+    if let Some(agent_id) = &filter.agent_id {
+        query = query.filter(dsl::agent_id.eq(agent_id));
+    }
+    if let Some(buyer) = &filter.buyer {
+        query = query.filter(dsl::buyer.eq(buyer));
+    }
+
+    query
+}
+
+/// Shop purchases matching `filter`, unordered.
+pub fn shop_purchases_matching(filter: &ShopPurchaseFilter) -> schema::ShopPurchase::BoxedQuery<'_, Pg> {
+    use schema::ShopPurchase::dsl;
+
+    let mut query = dsl::ShopPurchase.into_boxed();
+
+    if let Some(agent_id) = &filter.agent_id {
+        query = query.filter(dsl::agent_id.eq(agent_id));
+    }
+    if let Some(store_link) = &filter.store_link {
+        query = query.filter(dsl::store_link.eq(store_link));
+    }
+
+    query
+}
+
+pub async fn buy_offer(conn: &mut AsyncPgConnection, buy_offer_id: &str) -> Result<Option<BuyOffer>> {
+    use schema::BuyOffer::dsl;
+
+    dsl::BuyOffer
+        .filter(dsl::buy_offer_id.eq(buy_offer_id))
+        .select(BuyOffer::as_select())
+        .first(conn)
+        .await
+        .optional()
+        .map_err(Into::<Error>::into)
+}
+
+/// The buy offers among `buy_offer_ids` that were indexed, in no particular order.
+pub async fn buy_offers_by_ids(conn: &mut AsyncPgConnection, buy_offer_ids: &[String]) -> Result<Vec<BuyOffer>> {
+    use schema::BuyOffer::dsl;
+
+    dsl::BuyOffer
+        .filter(dsl::buy_offer_id.eq_any(buy_offer_ids))
+        .select(BuyOffer::as_select())
+        .load(conn)
+        .await
+        .map_err(Into::<Error>::into)
+}
+
+/// Buy offers matching `filter`, oldest first.
+pub async fn buy_offers(
+    conn: &mut AsyncPgConnection,
+    filter: &BuyOfferFilter,
+    page: &Page<i32>,
+) -> Result<Vec<BuyOffer>> {
+    use schema::BuyOffer::dsl;
+
+    let mut query = buy_offers_matching(filter).order(dsl::id).limit(page.limit);
+
+    if let Some(id) = page.after {
+        query = query.filter(dsl::id.gt(id));
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// Open buy offers for `product`, oldest first.
+pub async fn open_buy_offers_by_product(
+    conn: &mut AsyncPgConnection,
+    product: &str,
+    page: &Page<i32>,
+) -> Result<Vec<BuyOffer>> {
+    let filter = BuyOfferFilter {
+        product: Some(product.to_string()),
+        status: Some(BuyOfferStatus::Open.as_str().to_string()),
+        ..Default::default()
+    };
+
+    buy_offers(conn, &filter, page).await
+}
+
+pub async fn sell_offer(conn: &mut AsyncPgConnection, sell_offer_id: &str) -> Result<Option<SellOffer>> {
+    use schema::SellOffer::dsl;
+
+    dsl::SellOffer
+        .filter(dsl::sell_offer_id.eq(sell_offer_id))
+        .select(SellOffer::as_select())
+        .first(conn)
+        .await
+        .optional()
+        .map_err(Into::<Error>::into)
+}
+
+/// The sell offers among `sell_offer_ids` that were indexed, in no particular order.
+pub async fn sell_offers_by_ids(conn: &mut AsyncPgConnection, sell_offer_ids: &[String]) -> Result<Vec<SellOffer>> {
+    use schema::SellOffer::dsl;
+
+    dsl::SellOffer
+        .filter(dsl::sell_offer_id.eq_any(sell_offer_ids))
+        .select(SellOffer::as_select())
+        .load(conn)
+        .await
+        .map_err(Into::<Error>::into)
+}
+
+/// Sell offers matching `filter`, oldest first.
+pub async fn sell_offers(
+    conn: &mut AsyncPgConnection,
+    filter: &SellOfferFilter,
+    page: &Page<i32>,
+) -> Result<Vec<SellOffer>> {
+    use schema::SellOffer::dsl;
+
+    let mut query = sell_offers_matching(filter).order(dsl::id).limit(page.limit);
+
+    if let Some(id) = page.after {
+        query = query.filter(dsl::id.gt(id));
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// Sell offers made on `buy_offer_id`, cheapest first, whatever their status.
+pub async fn sell_offers_for_buy_offer(
+    conn: &mut AsyncPgConnection,
+    buy_offer_id: &str,
+    page: &Page<(BigDecimal, i32)>,
+) -> Result<Vec<SellOffer>> {
+    use schema::SellOffer::dsl;
+
+    let mut query = dsl::SellOffer
+        .filter(dsl::buy_offer_id.eq(buy_offer_id))
+        .select(SellOffer::as_select())
+        .order((dsl::price, dsl::id))
+        .limit(page.limit)
+        .into_boxed();
+
+    if let Some((price, id)) = &page.after {
+        query = query.filter(
+            dsl::price
+                .gt(price.clone())
+                .or(dsl::price.eq(price.clone()).and(dsl::id.gt(*id))),
+        );
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+pub async fn agent(conn: &mut AsyncPgConnection, agent_id: &str) -> Result<Option<Agent>> {
+    use schema::AgentWithStats::dsl;
+
+    dsl::AgentWithStats
+        .filter(dsl::agent_id.eq(agent_id))
+        .select(Agent::as_select())
+        .first(conn)
+        .await
+        .optional()
+        .map_err(Into::<Error>::into)
+}
+
+/// The agents among `agent_ids` that were indexed, in no particular order.
+pub async fn agents_by_ids(conn: &mut AsyncPgConnection, agent_ids: &[String]) -> Result<Vec<Agent>> {
+    use schema::AgentWithStats::dsl;
+
+    dsl::AgentWithStats
+        .filter(dsl::agent_id.eq_any(agent_ids))
+        .select(Agent::as_select())
+        .load(conn)
+        .await
+        .map_err(Into::<Error>::into)
+}
+
+/// Agents matching `filter`, in registration order.
+pub async fn agents(
+    conn: &mut AsyncPgConnection,
+    filter: &AgentFilter,
+    page: &Page<i32>,
+) -> Result<Vec<Agent>> {
+    use schema::AgentWithStats::dsl;
+
+    let mut query = agents_matching(filter).order(dsl::id).limit(page.limit);
+
+    if let Some(id) = page.after {
+        query = query.filter(dsl::id.gt(id));
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// The user registered by `owner_address`, if any.
+pub async fn user_by_owner_address(
+    conn: &mut AsyncPgConnection,
+    owner_address: &str,
+) -> Result<Option<User>> {
+    use schema::User::dsl;
+
+    dsl::User
+        .filter(dsl::user_owner_address.eq(owner_address))
+        .select(User::as_select())
+        .first(conn)
+        .await
+        .optional()
+        .map_err(Into::<Error>::into)
+}
+
+/// The users registered by any of `owner_addresses`, in no particular order.
+pub async fn users_by_owner_addresses(
+    conn: &mut AsyncPgConnection,
+    owner_addresses: &[String],
+) -> Result<Vec<User>> {
+    use schema::User::dsl;
+
+    dsl::User
+        .filter(dsl::user_owner_address.eq_any(owner_addresses))
+        .select(User::as_select())
+        .load(conn)
+        .await
+        .map_err(Into::<Error>::into)
+}
+
+/// Users matching `filter`, in registration order.
+pub async fn users(
+    conn: &mut AsyncPgConnection,
+    filter: &UserFilter,
+    page: &Page<i32>,
+) -> Result<Vec<User>> {
+    use schema::User::dsl;
+
+    let mut query = users_matching(filter).order(dsl::id).limit(page.limit);
+
+    if let Some(id) = page.after {
+        query = query.filter(dsl::id.gt(id));
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// Last checkpoint the concurrent pipeline `pipeline` has committed along
+/// with every earlier one, if it has committed any.
+pub async fn committed_checkpoint(conn: &mut AsyncPgConnection, pipeline: &str) -> Result<Option<i64>> {
+    use schema::watermarks::dsl;
+
+    dsl::watermarks
+        .filter(dsl::pipeline.eq(pipeline))
+        .select(dsl::checkpoint_hi_inclusive)
+        .first(conn)
+        .await
+        .optional()
+        .map_err(Into::<Error>::into)
+}
+
+/// Manual buys matching `filter`, oldest checkpoint first, up to the
+/// `manual_buys` pipeline's watermark. Keyed by [`ManualBuy::checkpoint_key`].
+pub async fn manual_buys(
+    conn: &mut AsyncPgConnection,
+    filter: &ManualBuyFilter,
+    page: &Page<(i64, i32)>,
+) -> Result<Vec<ManualBuy>> {
+    use schema::ManualBuy::dsl;
+
+    let Some(watermark) = committed_checkpoint(conn, ManualBuys::NAME).await? else {
+        return Ok(vec![]);
+    };
+
+    let mut query = manual_buys_matching(filter)
+        .filter(dsl::checkpoint.is_null().or(dsl::checkpoint.le(watermark)))
+        .order((dsl::checkpoint.asc().nulls_first(), dsl::id.asc()))
+        .limit(page.limit);
+
+    if let Some((checkpoint, id)) = page.after {
+        query = if checkpoint < 0 {
+            query.filter(dsl::checkpoint.is_not_null().or(dsl::id.gt(id)))
+        } else {
+            query.filter(
+                dsl::checkpoint
+                    .gt(checkpoint)
+                    .or(dsl::checkpoint.eq(checkpoint).and(dsl::id.gt(id))),
+            )
+        };
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// Shop purchases matching `filter`, oldest checkpoint first, up to the
+/// `shop_purchases` pipeline's watermark. Keyed by
+/// [`ShopPurchase::checkpoint_key`].
+pub async fn shop_purchases(
+    conn: &mut AsyncPgConnection,
+    filter: &ShopPurchaseFilter,
+    page: &Page<(i64, i32)>,
+) -> Result<Vec<ShopPurchase>> {
+    use schema::ShopPurchase::dsl;
+
+    let Some(watermark) = committed_checkpoint(conn, ShopPurchases::NAME).await? else {
+        return Ok(vec![]);
+    };
+
+    let mut query = shop_purchases_matching(filter)
+        .filter(dsl::checkpoint.is_null().or(dsl::checkpoint.le(watermark)))
+        .order((dsl::checkpoint.asc().nulls_first(), dsl::id.asc()))
+        .limit(page.limit);
+
+    if let Some((checkpoint, id)) = page.after {
+        query = if checkpoint < 0 {
+            query.filter(dsl::checkpoint.is_not_null().or(dsl::id.gt(id)))
+        } else {
+            query.filter(
+                dsl::checkpoint
+                    .gt(checkpoint)
+                    .or(dsl::checkpoint.eq(checkpoint).and(dsl::id.gt(id))),
+            )
+        };
+    }
+
+    query.load(conn).await.map_err(Into::<Error>::into)
+}
+
+/// Shop purchases made through `agent_id`, manual or automatic, oldest
+/// checkpoint first, as in [`shop_purchases`].
+pub async fn purchases_by_agent(
+    conn: &mut AsyncPgConnection,
+    agent_id: &str,
+    page: &Page<(i64, i32)>,
+) -> Result<Vec<ShopPurchase>> {
+    let filter = ShopPurchaseFilter {
+        agent_id: Some(agent_id.to_string()),
+        ..Default::default()
+    };
+
+    shop_purchases(conn, &filter, page).await
+}
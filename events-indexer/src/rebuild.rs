@@ -0,0 +1,275 @@
+use anyhow::{bail, Context, Error};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bool};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::RunQueryDsl;
+use log::info;
+use move_core_types::language_storage::StructTag;
+use std::sync::Arc;
+use sui_indexer_alt_framework::postgres::store::TransactionalStore;
+use sui_indexer_alt_framework::postgres::Db;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventOrigin, EventRegistry};
+use crate::handlers::{pipeline_of, run_jobs};
+use crate::jobs::{CheckpointJob, CheckpointTime};
+use crate::schema::{watermarks, RawEvent};
+
+/// Archived events loaded per query while replaying.
+const PAGE_SIZE: i64 = 1000;
+
+/// Advisory lock held shared by every running indexer and taken exclusively by
+/// a rebuild, so a table is never rebuilt under a pipeline writing to it.
+const INDEXER_LOCK: i64 = 0x7072_6963_656c_6573;
+
+#[derive(Debug)]
+pub struct RebuildReport {
+    pub table: String,
+    pub rows_before: i64,
+    pub rows_after: i64,
+    pub events_replayed: usize,
+}
+
+#[derive(QueryableByName)]
+struct RowCount {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct Locked {
+    #[diesel(sql_type = Bool)]
+    locked: bool,
+}
+
+/// Keep rebuilds from starting for as long as `conn` stays open, waiting for
+/// one that is already running to finish.
+pub async fn hold_indexer_lock<'a>(conn: &mut Connection<'a>) -> Result<()> {
+    diesel::sql_query("SELECT pg_advisory_lock_shared($1)")
+        .bind::<BigInt, _>(INDEXER_LOCK)
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    Ok(())
+}
+
+/// Empty `table` and replay the archived events of the handlers in
+/// `registry` that write to it, in chain order, in one transaction.
+///
+/// Checkpoint jobs writing to the table run after each replayed checkpoint and
+/// once more at the watermark of the pipeline writing the table. Only checkpoints with archived events
+/// are known here, so a time-based `closed_at` is the first such checkpoint at
+/// or after the deadline rather than the first checkpoint overall.
+///
+/// Rows are only inserted into `table`, even by handlers that also insert into
+/// other tables.
+/// The rebuild is refused while an indexer is running, see [`hold_indexer_lock`].
+///
+/// Unless `force` is set, the rebuild is refused when the table holds rows
+/// older than the archive, which would otherwise be lost. A rebuild that would
+/// leave rows of another table without their referent fails when it commits.
+pub async fn rebuild(
+    db: &Db,
+    mut registry: EventRegistry,
+    table: &str,
+    force: bool,
+) -> Result<RebuildReport> {
+    registry.only_writing(table)?;
+
+    let registry = &registry;
+    let names: Vec<&str> = registry.names().collect();
+    let jobs: Vec<Arc<dyn CheckpointJob>> = registry.jobs().cloned().collect();
+
+    db.transaction(|conn| {
+        async move {
+            let lock: Locked = diesel::sql_query("SELECT pg_try_advisory_xact_lock($1) AS locked")
+                .bind::<BigInt, _>(INDEXER_LOCK)
+                .get_result(conn)
+                .await
+                .map_err(Into::<Error>::into)?;
+
+            if !lock.locked {
+                bail!("An indexer is running against this database; stop it before rebuilding '{table}'");
+            }
+
+            let rows_before = count_rows(table, conn).await?;
+
+            if !force {
+                let unarchived = count_unarchived_rows(table, conn).await?;
+                if unarchived > 0 {
+                    bail!(
+                        "{unarchived} rows of '{table}' predate the RawEvent archive and cannot \
+                         be replayed; re-index from the first checkpoint or pass --force to drop them"
+                    );
+                }
+            }
+
+            clear_table(table, conn).await?;
+
+            let mut events_replayed = 0;
+            let mut cursor: Option<(i64, i64)> = None;
+            let mut current: Option<CheckpointTime> = None;
+
+            loop {
+                let mut query = RawEvent::table
+                    .filter(RawEvent::struct_name.eq_any(&names))
+                    .select((
+                        RawEvent::id,
+                        RawEvent::event_type,
+                        RawEvent::tx_digest,
+                        RawEvent::event_seq,
+                        RawEvent::checkpoint,
+                        RawEvent::checkpoint_timestamp_ms,
+                        RawEvent::bcs,
+                    ))
+                    .order((RawEvent::checkpoint, RawEvent::id))
+                    .limit(PAGE_SIZE)
+                    .into_boxed();
+
+                if let Some((checkpoint, id)) = cursor {
+                    query = query.filter(
+                        RawEvent::checkpoint
+                            .gt(checkpoint)
+                            .or(RawEvent::checkpoint.eq(checkpoint).and(RawEvent::id.gt(id))),
+                    );
+                }
+
+                let page: Vec<(i64, String, String, i64, i64, i64, Vec<u8>)> = query
+                    .load(conn)
+                    .await
+                    .map_err(Into::<Error>::into)?;
+
+                if page.is_empty() {
+                    break;
+                }
+
+                // Decode the page first so its rows can be inserted together.
+                let mut decoded = Vec::with_capacity(page.len());
+
+                for (id, event_type, tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms, bcs) in page {
+                    cursor = Some((checkpoint, id));
+
+                    let tag: StructTag = event_type
+                        .parse()
+                        .with_context(|| format!("Invalid event type '{event_type}' in RawEvent {id}"))?;
+
+                    let origin = EventOrigin {
+                        tx_digest,
+                        event_seq,
+                        checkpoint,
+                        checkpoint_timestamp_ms,
+                    };
+
+                    if let Some(projection) = registry.decode(&tag, &bcs, &origin) {
+                        decoded.push((origin, projection?));
+                    }
+                }
+
+                let mut batch = InsertBatch::default();
+                for (_, projection) in &decoded {
+                    projection.stage(&mut batch);
+                }
+                batch.retain_tables(&[table]);
+                batch.write(conn).await?;
+
+                for (origin, projection) in &decoded {
+                    if let Some(time) = current.filter(|t| t.checkpoint != origin.checkpoint) {
+                        run_jobs(&jobs, &[time], conn).await?;
+                    }
+
+                    current = Some(CheckpointTime {
+                        checkpoint: origin.checkpoint,
+                        timestamp_ms: origin.checkpoint_timestamp_ms,
+                    });
+
+                    projection.commit(conn).await?;
+                    events_replayed += 1;
+                }
+
+                info!("Rebuilding {table}: replayed {events_replayed} events");
+            }
+
+            if let Some(time) = current {
+                run_jobs(&jobs, &[time], conn).await?;
+            }
+
+            // Catch up with chain time past the last archived event.
+            let watermark: Option<(i64, i64)> = watermarks::table
+                .filter(watermarks::pipeline.eq(pipeline_of(table).unwrap_or_default()))
+                .select((
+                    watermarks::checkpoint_hi_inclusive,
+                    watermarks::timestamp_ms_hi_inclusive,
+                ))
+                .first(conn)
+                .await
+                .optional()
+                .map_err(Into::<Error>::into)?;
+
+            if let Some((checkpoint, timestamp_ms)) = watermark {
+                run_jobs(&jobs, &[CheckpointTime { checkpoint, timestamp_ms }], conn).await?;
+            }
+
+            let rows_after = count_rows(table, conn).await?;
+
+            Ok(RebuildReport {
+                table: table.to_string(),
+                rows_before,
+                rows_after,
+                events_replayed,
+            })
+        }
+        .scope_boxed()
+    })
+    .await
+}
+
+/// Delete every row of `table` and restart its ids. Other tables may reference
+/// its rows, which rules out `TRUNCATE`; the foreign key from `SellOffer` is
+/// deferred, so it is checked against the replayed rows when the rebuild commits.
+async fn clear_table<'a>(table: &str, conn: &mut Connection<'a>) -> Result<()> {
+    diesel::sql_query("SET CONSTRAINTS ALL DEFERRED")
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    diesel::sql_query(format!(r#"DELETE FROM "{table}""#))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    diesel::sql_query(format!(
+        r#"SELECT setval(pg_get_serial_sequence('"{table}"', 'id'), 1, false)"#
+    ))
+    .execute(conn)
+    .await
+    .map_err(Into::<Error>::into)?;
+
+    Ok(())
+}
+
+async fn count_rows<'a>(table: &str, conn: &mut Connection<'a>) -> Result<i64> {
+    let row: RowCount = diesel::sql_query(format!(r#"SELECT COUNT(*) AS count FROM "{table}""#))
+        .get_result(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    Ok(row.count)
+}
+
+/// Rows indexed before their event could have been archived.
+async fn count_unarchived_rows<'a>(table: &str, conn: &mut Connection<'a>) -> Result<i64> {
+    let row: RowCount = diesel::sql_query(format!(
+        r#"
+        SELECT COUNT(*) AS count FROM "{table}"
+        WHERE checkpoint IS NULL
+            OR checkpoint < (SELECT COALESCE(MIN(checkpoint), 9223372036854775807) FROM "RawEvent")
+        "#
+    ))
+    .get_result(conn)
+    .await
+    .map_err(Into::<Error>::into)?;
+
+    Ok(row.count)
+}
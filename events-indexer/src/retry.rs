@@ -0,0 +1,168 @@
+use anyhow::{Context, Error};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::RunQueryDsl;
+use log::{info, warn};
+use move_core_types::language_storage::StructTag;
+use sui_indexer_alt_framework::postgres::store::{Store, TransactionalStore};
+use sui_indexer_alt_framework::postgres::Db;
+use sui_indexer_alt_framework::Result;
+
+use crate::events::batch::InsertBatch;
+use crate::events::{Connection, EventOrigin, EventRegistry};
+use crate::schema::{FailedEvent, RawEvent};
+
+/// Failed events loaded per query while retrying.
+const PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug, Default)]
+pub struct RetryReport {
+    /// Decoded and projected; marked resolved.
+    pub resolved: usize,
+    /// Still failing; the error and attempt count are updated.
+    pub failed: usize,
+    /// No registered handler, e.g. because the event type is disabled.
+    pub skipped: usize,
+}
+
+type FailedEventRow = (i64, String, String, i64, i64, i64, Vec<u8>);
+
+/// Decode and project every unresolved `FailedEvent` again, in chain order,
+/// each in its own transaction.
+///
+/// Retried events are applied after everything indexed since they failed, so
+/// tables whose state depends on event order should be rebuilt afterwards.
+pub async fn retry_failed(db: &Db, registry: &EventRegistry) -> Result<RetryReport> {
+    let mut report = RetryReport::default();
+    let mut cursor: Option<(i64, i64)> = None;
+
+    loop {
+        let page: Vec<FailedEventRow> = {
+            let mut conn = db.connect().await?;
+
+            let mut query = FailedEvent::table
+                .filter(FailedEvent::resolved_at.is_null())
+                .select((
+                    FailedEvent::id,
+                    FailedEvent::event_type,
+                    FailedEvent::tx_digest,
+                    FailedEvent::event_seq,
+                    FailedEvent::checkpoint,
+                    FailedEvent::checkpoint_timestamp_ms,
+                    FailedEvent::bcs,
+                ))
+                .order((FailedEvent::checkpoint, FailedEvent::id))
+                .limit(PAGE_SIZE)
+                .into_boxed();
+
+            if let Some((checkpoint, id)) = cursor {
+                query = query.filter(
+                    FailedEvent::checkpoint
+                        .gt(checkpoint)
+                        .or(FailedEvent::checkpoint.eq(checkpoint).and(FailedEvent::id.gt(id))),
+                );
+            }
+
+            query.load(&mut conn).await.map_err(Into::<Error>::into)?
+        };
+
+        if page.is_empty() {
+            break;
+        }
+
+        for row in &page {
+            let (id, _, _, _, checkpoint, _, _) = row;
+            cursor = Some((*checkpoint, *id));
+
+            let retried = db
+                .transaction(|conn| async move { retry_one(registry, row, conn).await }.scope_boxed())
+                .await;
+
+            match retried {
+                Ok(true) => report.resolved += 1,
+                Ok(false) => report.skipped += 1,
+                Err(e) => {
+                    warn!("FailedEvent {id} still fails: {e:#}");
+                    record_attempt(db, *id, format!("{e:#}")).await?;
+                    report.failed += 1;
+                }
+            }
+        }
+
+        info!(
+            "Retried failed events: {} resolved, {} failed, {} skipped",
+            report.resolved, report.failed, report.skipped
+        );
+    }
+
+    Ok(report)
+}
+
+/// Project one failed event, returning false when no handler is registered
+/// for it.
+async fn retry_one<'a>(
+    registry: &EventRegistry,
+    row: &FailedEventRow,
+    conn: &mut Connection<'a>,
+) -> Result<bool> {
+    let (id, event_type, tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms, bcs) = row;
+
+    let tag: StructTag = event_type
+        .parse()
+        .with_context(|| format!("Invalid event type '{event_type}' in FailedEvent {id}"))?;
+
+    let origin = EventOrigin {
+        tx_digest: tx_digest.clone(),
+        event_seq: *event_seq,
+        checkpoint: *checkpoint,
+        checkpoint_timestamp_ms: *checkpoint_timestamp_ms,
+    };
+
+    let Some(projection) = registry.decode(&tag, bcs, &origin) else {
+        return Ok(false);
+    };
+
+    let projection = projection?;
+    let mut batch = InsertBatch::default();
+    projection.stage(&mut batch);
+    batch.write(conn).await?;
+    projection.commit(conn).await?;
+
+    let json = registry.render(&tag, bcs).transpose()?;
+
+    diesel::update(
+        RawEvent::table
+            .filter(RawEvent::tx_digest.eq(tx_digest))
+            .filter(RawEvent::event_seq.eq(event_seq)),
+    )
+    .set(RawEvent::json.eq(json))
+    .execute(conn)
+    .await
+    .map_err(Into::<Error>::into)?;
+
+    diesel::update(FailedEvent::table.filter(FailedEvent::id.eq(id)))
+        .set((
+            FailedEvent::attempts.eq(FailedEvent::attempts + 1),
+            FailedEvent::resolved_at.eq(diesel::dsl::now.nullable()),
+        ))
+        .execute(conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    Ok(true)
+}
+
+async fn record_attempt(db: &Db, id: i64, error: String) -> Result<()> {
+    let mut conn = db.connect().await?;
+
+    diesel::update(FailedEvent::table.filter(FailedEvent::id.eq(id)))
+        .set((
+            FailedEvent::attempts.eq(FailedEvent::attempts + 1),
+            FailedEvent::error.eq(error),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(Into::<Error>::into)?;
+
+    Ok(())
+}
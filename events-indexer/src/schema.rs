@@ -6,11 +6,53 @@ diesel::table! {
         agent_id -> Text,
         agent_address -> Text,
         agent_owner_address -> Text,
-        stake_amount -> Int8,
-        rating -> Int8,
-        buys -> Int8,
+        stake_amount -> Numeric,
+        rating -> Numeric,
         active -> Bool,
-        registered_at -> Int8,
+        registered_at -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+    }
+}
+
+// A view, which Diesel CLI does not print: `Agent` with statistics computed
+// from `ShopPurchase`. Read-only.
+diesel::table! {
+    AgentWithStats (id) {
+        id -> Int4,
+        agent_id -> Text,
+        agent_address -> Text,
+        agent_owner_address -> Text,
+        stake_amount -> Numeric,
+        rating -> Numeric,
+        active -> Bool,
+        registered_at -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+        buys -> Int8,
+        total_fees_earned -> Numeric,
+        total_volume -> Numeric,
+    }
+}
+
+diesel::table! {
+    AgentHistory (id) {
+        id -> Int4,
+        agent_id -> Text,
+        change -> Text,
+        stake_delta -> Nullable<Numeric>,
+        stake_amount -> Nullable<Numeric>,
+        active -> Nullable<Bool>,
+        rating -> Nullable<Numeric>,
+        changed_at -> Numeric,
+        tx_digest -> Text,
+        event_seq -> Int8,
+        checkpoint -> Int8,
+        checkpoint_timestamp_ms -> Int8,
     }
 }
 
@@ -20,10 +62,56 @@ diesel::table! {
         buy_offer_id -> Text,
         owner -> Text,
         product -> Text,
-        price -> Int8,
+        price -> Numeric,
         offer_type_is_time_based -> Bool,
-        deadline -> Int8,
-        created_at -> Int8,
+        deadline -> Numeric,
+        created_at -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+        status -> Text,
+        closed_at -> Nullable<Int8>,
+        remaining_balance -> Nullable<Numeric>,
+        accepted_sell_offer_id -> Nullable<Text>,
+        total_paid -> Nullable<Numeric>,
+        filled_at -> Nullable<Int8>,
+        updated_at -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    BuyOfferPriceChange (id) {
+        id -> Int4,
+        buy_offer_id -> Text,
+        owner -> Text,
+        old_price -> Numeric,
+        new_price -> Numeric,
+        price_reduction -> Numeric,
+        tx_digest -> Text,
+        event_seq -> Int8,
+        checkpoint -> Int8,
+        checkpoint_timestamp_ms -> Int8,
+    }
+}
+
+diesel::table! {
+    FailedEvent (id) {
+        id -> Int8,
+        package_id -> Text,
+        module -> Text,
+        struct_name -> Text,
+        event_type -> Text,
+        tx_digest -> Text,
+        event_seq -> Int8,
+        sender -> Text,
+        checkpoint -> Int8,
+        checkpoint_timestamp_ms -> Int8,
+        bcs -> Bytea,
+        error -> Text,
+        attempts -> Int4,
+        failed_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
     }
 }
 
@@ -35,9 +123,30 @@ diesel::table! {
         agent_id -> Text,
         sell_offer_id -> Text,
         store_link -> Text,
-        product_price -> Int8,
-        agent_fee -> Int8,
-        total_paid -> Int8,
+        product_price -> Numeric,
+        agent_fee -> Numeric,
+        total_paid -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    RawEvent (id) {
+        id -> Int8,
+        package_id -> Text,
+        module -> Text,
+        struct_name -> Text,
+        event_type -> Text,
+        tx_digest -> Text,
+        event_seq -> Int8,
+        sender -> Text,
+        checkpoint -> Int8,
+        checkpoint_timestamp_ms -> Int8,
+        bcs -> Bytea,
+        json -> Nullable<Jsonb>,
     }
 }
 
@@ -49,8 +158,32 @@ diesel::table! {
         agent_id -> Text,
         agent_address -> Text,
         store_link -> Text,
-        price -> Int8,
+        price -> Numeric,
+        is_update -> Bool,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+        status -> Text,
+        closed_at -> Nullable<Int8>,
+        updated_at -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    SellOfferRevision (id) {
+        id -> Int4,
+        sell_offer_id -> Text,
+        buy_offer_id -> Text,
+        agent_id -> Text,
+        agent_address -> Text,
+        store_link -> Text,
+        price -> Numeric,
         is_update -> Bool,
+        tx_digest -> Text,
+        event_seq -> Int8,
+        checkpoint -> Int8,
+        checkpoint_timestamp_ms -> Int8,
     }
 }
 
@@ -59,9 +192,13 @@ diesel::table! {
         id -> Int4,
         agent_id -> Text,
         store_link -> Text,
-        product_price -> Int8,
-        agent_fee -> Int8,
-        platform_fee -> Int8,
+        product_price -> Numeric,
+        agent_fee -> Numeric,
+        platform_fee -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
     }
 }
 
@@ -71,10 +208,16 @@ diesel::table! {
         user_id -> Text,
         user_address -> Text,
         user_owner_address -> Text,
-        subscription_fee -> Int8,
-        subscription_deadline -> Int8,
+        subscription_fee -> Numeric,
+        subscription_deadline -> Numeric,
         active -> Bool,
-        registered_at -> Int8,
+        registered_at -> Numeric,
+        tx_digest -> Nullable<Text>,
+        event_seq -> Nullable<Int8>,
+        checkpoint -> Nullable<Int8>,
+        checkpoint_timestamp_ms -> Nullable<Int8>,
+        subscription_cancelled_at -> Nullable<Numeric>,
+        updated_at -> Nullable<Int8>,
     }
 }
 
@@ -93,9 +236,15 @@ diesel::table! {
 
 diesel::allow_tables_to_appear_in_same_query!(
     Agent,
+    AgentHistory,
+    AgentWithStats,
     BuyOffer,
+    BuyOfferPriceChange,
+    FailedEvent,
     ManualBuy,
+    RawEvent,
     SellOffer,
+    SellOfferRevision,
     ShopPurchase,
     User,
     watermarks,
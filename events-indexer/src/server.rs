@@ -0,0 +1,344 @@
+//! Read-only JSON API over the projection tables.
+//!
+//! Lists return `{ "items": [...], "next_cursor": ... }`; pass `next_cursor`
+//! back as `cursor` for the following page. It is `null` on the last page.
+
+use anyhow::Context;
+use axum::extract::{Path, Query, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use bigdecimal::BigDecimal;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use sui_indexer_alt_framework::postgres::store::Store;
+use sui_indexer_alt_framework::postgres::Db;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::models::{Agent, BuyOffer, ManualBuy, SellOffer, ShopPurchase, User};
+use crate::queries::{
+    self, AgentFilter, BuyOfferFilter, ManualBuyFilter, Page, SellOfferFilter, ShopPurchaseFilter,
+    UserFilter, DEFAULT_PAGE_SIZE,
+};
+
+/// Most rows a client can ask for in one page.
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+/// Routes of the API, reading from `db`. With the `graphql` feature, this
+/// includes `/graphql`.
+///
+/// Browsers may call it from `allowed_origins` (e.g. `https://app.example.com`),
+/// from any origin if they include `*`, and from none if they are empty.
+pub fn router(db: Db, allowed_origins: &[String]) -> anyhow::Result<Router> {
+    let cors = CorsLayer::new()
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([CONTENT_TYPE])
+        .allow_origin(allow_origin(allowed_origins)?);
+
+    let router = Router::new()
+        .route("/buy-offers", get(list_buy_offers))
+        .route("/buy-offers/:buy_offer_id", get(get_buy_offer))
+        .route("/buy-offers/:buy_offer_id/sell-offers", get(list_buy_offer_sell_offers))
+        .route("/sell-offers", get(list_sell_offers))
+        .route("/sell-offers/:sell_offer_id", get(get_sell_offer))
+        .route("/agents", get(list_agents))
+        .route("/agents/:agent_id", get(get_agent))
+        .route("/agents/:agent_id/purchases", get(list_agent_purchases))
+        .route("/users", get(list_users))
+        .route("/users/:owner_address", get(get_user))
+        .route("/manual-buys", get(list_manual_buys))
+        .route("/shop-purchases", get(list_shop_purchases))
+        .with_state(db.clone());
+
+    #[cfg(feature = "graphql")]
+    let router = router.merge(crate::graphql::router(db));
+
+    Ok(router.layer(cors))
+}
+
+fn allow_origin(allowed_origins: &[String]) -> anyhow::Result<AllowOrigin> {
+    if allowed_origins.iter().any(|o| o == "*") {
+        return Ok(AllowOrigin::any());
+    }
+
+    let origins = allowed_origins
+        .iter()
+        .map(|o| HeaderValue::from_str(o).with_context(|| format!("Invalid CORS origin '{o}'")))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(AllowOrigin::list(origins))
+}
+
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(e) => {
+                error!("Request failed: {e:#}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string())
+            }
+        };
+
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Deserialize)]
+struct PageParams {
+    cursor: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct PageResponse<T> {
+    items: Vec<T>,
+    next_cursor: Option<String>,
+}
+
+impl PageParams {
+    fn limit(&self) -> Result<i64, ApiError> {
+        match self.limit {
+            None => Ok(DEFAULT_PAGE_SIZE),
+            Some(limit) if (1..=MAX_PAGE_SIZE).contains(&limit) => Ok(limit),
+            Some(limit) => Err(ApiError::BadRequest(format!(
+                "limit must be between 1 and {MAX_PAGE_SIZE}, got {limit}"
+            ))),
+        }
+    }
+
+    /// Page of a list keyed by `id`.
+    fn by_id(&self) -> Result<Page<i32>, ApiError> {
+        let after = self
+            .cursor
+            .as_deref()
+            .map(|cursor| cursor.parse().map_err(|_| invalid_cursor(cursor)))
+            .transpose()?;
+
+        Ok(Page {
+            after,
+            limit: self.limit()?,
+        })
+    }
+
+    /// Page of a list keyed by `(price, id)`.
+    fn by_price(&self) -> Result<Page<(BigDecimal, i32)>, ApiError> {
+        let after = self
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                let (price, id) = cursor.split_once(':').ok_or_else(|| invalid_cursor(cursor))?;
+                let price = BigDecimal::from_str(price).map_err(|_| invalid_cursor(cursor))?;
+                let id = id.parse().map_err(|_| invalid_cursor(cursor))?;
+                Ok((price, id))
+            })
+            .transpose()?;
+
+        Ok(Page {
+            after,
+            limit: self.limit()?,
+        })
+    }
+
+    /// Page of a list keyed by `(checkpoint, id)`.
+    fn by_checkpoint(&self) -> Result<Page<(i64, i32)>, ApiError> {
+        let after = self
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                let (checkpoint, id) = cursor.split_once(':').ok_or_else(|| invalid_cursor(cursor))?;
+                let checkpoint = checkpoint.parse().map_err(|_| invalid_cursor(cursor))?;
+                let id = id.parse().map_err(|_| invalid_cursor(cursor))?;
+                Ok((checkpoint, id))
+            })
+            .transpose()?;
+
+        Ok(Page {
+            after,
+            limit: self.limit()?,
+        })
+    }
+}
+
+fn invalid_cursor(cursor: &str) -> ApiError {
+    ApiError::BadRequest(format!("Invalid cursor '{cursor}'"))
+}
+
+/// The response for one page of rows, with a cursor after the last row when
+/// the page is full.
+fn page_of<T>(items: Vec<T>, limit: i64, cursor: impl Fn(&T) -> String) -> PageResponse<T> {
+    let next_cursor = (items.len() as i64 == limit)
+        .then(|| items.last().map(cursor))
+        .flatten();
+
+    PageResponse { items, next_cursor }
+}
+
+fn found<T>(row: Option<T>, what: &str, id: &str) -> ApiResult<T> {
+    row.map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("No {what} '{id}'")))
+}
+
+async fn list_buy_offers(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<BuyOfferFilter>,
+) -> ApiResult<PageResponse<BuyOffer>> {
+    let page = params.by_id()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::buy_offers(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| r.id.to_string())))
+}
+
+async fn get_buy_offer(State(db): State<Db>, Path(buy_offer_id): Path<String>) -> ApiResult<BuyOffer> {
+    let mut conn = db.connect().await?;
+    let row = queries::buy_offer(&mut conn, &buy_offer_id).await?;
+    found(row, "buy offer", &buy_offer_id)
+}
+
+/// Sell offers on one buy offer, cheapest first.
+async fn list_buy_offer_sell_offers(
+    State(db): State<Db>,
+    Path(buy_offer_id): Path<String>,
+    Query(params): Query<PageParams>,
+) -> ApiResult<PageResponse<SellOffer>> {
+    let page = params.by_price()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::sell_offers_for_buy_offer(&mut conn, &buy_offer_id, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| format!("{}:{}", r.price, r.id))))
+}
+
+async fn list_sell_offers(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<SellOfferFilter>,
+) -> ApiResult<PageResponse<SellOffer>> {
+    let page = params.by_id()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::sell_offers(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| r.id.to_string())))
+}
+
+async fn get_sell_offer(State(db): State<Db>, Path(sell_offer_id): Path<String>) -> ApiResult<SellOffer> {
+    let mut conn = db.connect().await?;
+    let row = queries::sell_offer(&mut conn, &sell_offer_id).await?;
+    found(row, "sell offer", &sell_offer_id)
+}
+
+async fn list_agents(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<AgentFilter>,
+) -> ApiResult<PageResponse<Agent>> {
+    let page = params.by_id()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::agents(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| r.id.to_string())))
+}
+
+async fn get_agent(State(db): State<Db>, Path(agent_id): Path<String>) -> ApiResult<Agent> {
+    let mut conn = db.connect().await?;
+    let row = queries::agent(&mut conn, &agent_id).await?;
+    found(row, "agent", &agent_id)
+}
+
+async fn list_agent_purchases(
+    State(db): State<Db>,
+    Path(agent_id): Path<String>,
+    Query(params): Query<PageParams>,
+) -> ApiResult<PageResponse<ShopPurchase>> {
+    let page = params.by_checkpoint()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::purchases_by_agent(&mut conn, &agent_id, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| {
+        let (checkpoint, id) = r.checkpoint_key();
+        format!("{checkpoint}:{id}")
+    })))
+}
+
+async fn list_users(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<UserFilter>,
+) -> ApiResult<PageResponse<User>> {
+    let page = params.by_id()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::users(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| r.id.to_string())))
+}
+
+async fn get_user(State(db): State<Db>, Path(owner_address): Path<String>) -> ApiResult<User> {
+    let mut conn = db.connect().await?;
+    let row = queries::user_by_owner_address(&mut conn, &owner_address).await?;
+    found(row, "user owned by", &owner_address)
+}
+
+async fn list_manual_buys(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<ManualBuyFilter>,
+) -> ApiResult<PageResponse<ManualBuy>> {
+    let page = params.by_checkpoint()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::manual_buys(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| {
+        let (checkpoint, id) = r.checkpoint_key();
+        format!("{checkpoint}:{id}")
+    })))
+}
+
+async fn list_shop_purchases(
+    State(db): State<Db>,
+    Query(params): Query<PageParams>,
+    Query(filter): Query<ShopPurchaseFilter>,
+) -> ApiResult<PageResponse<ShopPurchase>> {
+    let page = params.by_checkpoint()?;
+
+    let mut conn = db.connect().await?;
+    let rows = queries::shop_purchases(&mut conn, &filter, &page).await?;
+    Ok(Json(page_of(rows, page.limit, |r| {
+        let (checkpoint, id) = r.checkpoint_key();
+        format!("{checkpoint}:{id}")
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origins(origins: &[&str]) -> Vec<String> {
+        origins.iter().map(|o| o.to_string()).collect()
+    }
+
+    #[test]
+    fn cors_origins_must_be_header_values() {
+        assert!(allow_origin(&origins(&[])).is_ok());
+        assert!(allow_origin(&origins(&["*"])).is_ok());
+        assert!(allow_origin(&origins(&["https://app.example.com", "http://localhost:3000"])).is_ok());
+        assert!(allow_origin(&origins(&["https://app.example.com\n"])).is_err());
+    }
+}
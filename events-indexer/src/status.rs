@@ -0,0 +1,42 @@
+//! Lifecycle states stored in the `status` columns of `BuyOffer` and `SellOffer`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuyOfferStatus {
+    Open,
+    Cancelled,
+    Fulfilled,
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SellOfferStatus {
+    Open,
+    /// The buy offer closed without being bought through a sell offer.
+    Closed,
+    /// The sell offer the buyer bought through.
+    Accepted,
+    /// Lost to another sell offer on the same buy offer.
+    Rejected,
+}
+
+impl BuyOfferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuyOfferStatus::Open => "open",
+            BuyOfferStatus::Cancelled => "cancelled",
+            BuyOfferStatus::Fulfilled => "fulfilled",
+            BuyOfferStatus::Expired => "expired",
+        }
+    }
+}
+
+impl SellOfferStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SellOfferStatus::Open => "open",
+            SellOfferStatus::Closed => "closed",
+            SellOfferStatus::Accepted => "accepted",
+            SellOfferStatus::Rejected => "rejected",
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Agent registration and statistics against a scratch database.
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use common::{address, id, index, origin, ScratchDb};
+use events_indexer::events::agent_registered::{AgentRegisteredEvent, AgentRegisteredHandler};
+use events_indexer::events::shop_purchase::{ShopPurchaseEvent, ShopPurchaseHandler};
+use events_indexer::events::Connection;
+use events_indexer::models::Agent;
+use events_indexer::queries;
+
+const AGENT: u8 = 0x11;
+
+fn agent_registered() -> AgentRegisteredEvent {
+    AgentRegisteredEvent {
+        agent_id: id(AGENT),
+        agent_object_address: address(AGENT),
+        agent_owner_address: address(0x30),
+        stake_amount: 500,
+        timestamp: 1,
+    }
+}
+
+fn shop_purchase(agent: u8, product_price: u64, agent_fee: u64) -> ShopPurchaseEvent {
+    ShopPurchaseEvent {
+        agent_id: id(agent),
+        store_link: "https://example.com/item".to_string(),
+        product_price,
+        agent_fee,
+        platform_fee: 1,
+    }
+}
+
+async fn agent(conn: &mut Connection<'_>) -> Agent {
+    queries::agent(conn, &id(AGENT).to_string()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn statistics_count_the_agents_shop_purchases() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    index(&mut conn, &AgentRegisteredHandler::default(), &agent_registered(), origin(10, 0)).await;
+
+    let fresh = agent(&mut conn).await;
+    assert_eq!((fresh.buys, fresh.total_volume), (0, BigDecimal::from(0)));
+
+    index(&mut conn, &ShopPurchaseHandler, &shop_purchase(AGENT, 900, 20), origin(20, 0)).await;
+    index(&mut conn, &ShopPurchaseHandler, &shop_purchase(AGENT, 100, 5), origin(30, 0)).await;
+    index(&mut conn, &ShopPurchaseHandler, &shop_purchase(0x12, 700, 7), origin(30, 1)).await;
+
+    let agent = agent(&mut conn).await;
+    assert_eq!(agent.buys, 2);
+    assert_eq!(agent.total_volume, BigDecimal::from(1_000));
+    assert_eq!(agent.total_fees_earned, BigDecimal::from(25));
+}
+
+#[tokio::test]
+async fn purchases_indexed_before_the_registration_count() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    // The shop_purchases pipeline may run ahead of the agents pipeline.
+    index(&mut conn, &ShopPurchaseHandler, &shop_purchase(AGENT, 900, 20), origin(20, 0)).await;
+    index(&mut conn, &AgentRegisteredHandler::default(), &agent_registered(), origin(10, 0)).await;
+
+    assert_eq!(agent(&mut conn).await.buys, 1);
+}
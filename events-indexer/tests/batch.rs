@@ -0,0 +1,119 @@
+//! `InsertBatch::write` against a scratch database.
+
+mod common;
+
+use std::borrow::Cow;
+
+use bigdecimal::BigDecimal;
+use common::ScratchDb;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use events_indexer::events::batch::InsertBatch;
+use events_indexer::events::shop_purchase::ShopPurchaseValue;
+use events_indexer::events::user_registered::UserValue;
+use events_indexer::events::Connection;
+use events_indexer::schema::{ShopPurchase, User};
+
+fn shop_purchase(seq: i64) -> ShopPurchaseValue {
+    ShopPurchaseValue {
+        agent_id: "0xa".to_string(),
+        store_link: "https://example.com/item".to_string(),
+        product_price: BigDecimal::from(100),
+        agent_fee: BigDecimal::from(5),
+        platform_fee: BigDecimal::from(1),
+        tx_digest: "tx".to_string(),
+        event_seq: seq,
+        checkpoint: 10,
+        checkpoint_timestamp_ms: 10_000,
+    }
+}
+
+fn user() -> UserValue {
+    UserValue {
+        user_id: "0x1".to_string(),
+        user_address: "0x2".to_string(),
+        user_owner_address: "0x3".to_string(),
+        subscription_fee: BigDecimal::from(10),
+        subscription_deadline: BigDecimal::from(50_000),
+        active: true,
+        registered_at: BigDecimal::from(10_000),
+        tx_digest: "tx".to_string(),
+        event_seq: 0,
+        checkpoint: 10,
+        checkpoint_timestamp_ms: 10_000,
+    }
+}
+
+async fn shop_purchases(conn: &mut Connection<'_>) -> i64 {
+    ShopPurchase::table.count().get_result(conn).await.unwrap()
+}
+
+#[tokio::test]
+async fn rows_beyond_one_statement_are_written_in_chunks() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    // More rows than `write` puts in one statement.
+    let batch = InsertBatch {
+        shop_purchases: (0..5_000).map(|seq| Cow::Owned(shop_purchase(seq))).collect(),
+        ..Default::default()
+    };
+
+    assert_eq!(batch.write(&mut conn).await.unwrap(), 5_000);
+    assert_eq!(shop_purchases(&mut conn).await, 5_000);
+}
+
+#[tokio::test]
+async fn rewriting_a_batch_inserts_nothing() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    let batch = InsertBatch {
+        users: vec![Cow::Owned(user())],
+        shop_purchases: (0..3).map(|seq| Cow::Owned(shop_purchase(seq))).collect(),
+        ..Default::default()
+    };
+
+    assert_eq!(batch.write(&mut conn).await.unwrap(), 4);
+    assert_eq!(batch.write(&mut conn).await.unwrap(), 0);
+    assert_eq!(shop_purchases(&mut conn).await, 3);
+}
+
+#[tokio::test]
+async fn only_retained_tables_are_written() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    let mut batch = InsertBatch {
+        users: vec![Cow::Owned(user())],
+        shop_purchases: vec![Cow::Owned(shop_purchase(1))],
+        ..Default::default()
+    };
+    batch.retain_tables(&["User"]);
+
+    assert_eq!(batch.write(&mut conn).await.unwrap(), 1);
+    assert_eq!(User::table.count().get_result::<i64>(&mut conn).await.unwrap(), 1);
+    assert_eq!(shop_purchases(&mut conn).await, 0);
+}
+
+#[tokio::test]
+async fn rows_conflict_on_their_on_chain_id() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    // A second registration from another event, which the unique index on
+    // the owner address would otherwise reject.
+    let again = UserValue { tx_digest: "tx2".to_string(), ..user() };
+    let batch = InsertBatch {
+        users: vec![Cow::Owned(user()), Cow::Owned(again)],
+        ..Default::default()
+    };
+
+    assert_eq!(batch.write(&mut conn).await.unwrap(), 1);
+    let digests: Vec<Option<String>> = User::table.select(User::tx_digest).load(&mut conn).await.unwrap();
+    assert_eq!(digests, [Some("tx".to_string())]);
+}
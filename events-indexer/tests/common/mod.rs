@@ -0,0 +1,145 @@
+//! A scratch database for the integration tests, created on the Postgres
+//! server at `TEST_DATABASE_URL` and dropped afterwards. Tests are skipped when
+//! it is not set.
+
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use diesel::{Connection, PgConnection};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use diesel_migrations::MigrationHarness;
+use events_indexer::events::batch::InsertBatch;
+use events_indexer::events::{self, EventHandler, EventOrigin};
+use events_indexer::MIGRATIONS;
+use sui_indexer_alt_framework::postgres::{Db, DbArgs};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use url::Url;
+
+static DATABASES: AtomicUsize = AtomicUsize::new(0);
+
+/// A migrated database, dropped with this value.
+pub struct ScratchDb {
+    admin_url: String,
+    name: String,
+    url: String,
+}
+
+impl ScratchDb {
+    pub fn create() -> Option<Self> {
+        let Ok(admin_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set, skipping");
+            return None;
+        };
+
+        let name = format!(
+            "events_indexer_test_{}_{}",
+            std::process::id(),
+            DATABASES.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let mut admin = PgConnection::establish(&admin_url).expect("Failed to connect to TEST_DATABASE_URL");
+        run_sync(&mut admin, &format!(r#"CREATE DATABASE "{name}""#));
+
+        let mut url = Url::parse(&admin_url).unwrap();
+        url.set_path(&name);
+        let url = url.to_string();
+
+        let mut conn = PgConnection::establish(&url).unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        // Created by the indexer framework rather than our migrations.
+        run_sync(
+            &mut conn,
+            r#"
+            CREATE TABLE watermarks (
+                pipeline TEXT PRIMARY KEY,
+                epoch_hi_inclusive BIGINT NOT NULL,
+                checkpoint_hi_inclusive BIGINT NOT NULL,
+                tx_hi BIGINT NOT NULL,
+                timestamp_ms_hi_inclusive BIGINT NOT NULL,
+                reader_lo BIGINT NOT NULL,
+                pruner_timestamp TIMESTAMP NOT NULL DEFAULT NOW(),
+                pruner_hi BIGINT NOT NULL
+            )
+            "#,
+        );
+
+        Some(Self { admin_url, name, url })
+    }
+
+    pub async fn connect(&self) -> AsyncPgConnection {
+        AsyncPgConnection::establish(&self.url).await.unwrap()
+    }
+
+    /// A connection pool like the indexer's, for handlers, jobs and batches.
+    pub async fn db(&self) -> Db {
+        Db::for_write(Url::parse(&self.url).unwrap(), DbArgs::default()).await.unwrap()
+    }
+}
+
+impl Drop for ScratchDb {
+    fn drop(&mut self) {
+        if let Ok(mut admin) = PgConnection::establish(&self.admin_url) {
+            let sql = format!(r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#, self.name);
+            let _ = diesel::RunQueryDsl::execute(diesel::sql_query(sql), &mut admin);
+        }
+    }
+}
+
+fn run_sync(conn: &mut PgConnection, sql: &str) {
+    diesel::RunQueryDsl::execute(diesel::sql_query(sql), conn).unwrap();
+}
+
+pub async fn run(conn: &mut AsyncPgConnection, sql: &str) {
+    diesel::sql_query(sql).execute(conn).await.unwrap();
+}
+
+pub async fn set_watermark(conn: &mut AsyncPgConnection, pipeline: &str, checkpoint: i64) {
+    run(
+        conn,
+        &format!(
+            "INSERT INTO watermarks \
+             (pipeline, epoch_hi_inclusive, checkpoint_hi_inclusive, tx_hi, timestamp_ms_hi_inclusive, reader_lo, pruner_hi) \
+             VALUES ('{pipeline}', 0, {checkpoint}, 0, 0, 0, 0) \
+             ON CONFLICT (pipeline) DO UPDATE SET checkpoint_hi_inclusive = excluded.checkpoint_hi_inclusive"
+        ),
+    )
+    .await;
+}
+
+/// The `seq`th event of a transaction at `checkpoint`, whose timestamp is the
+/// checkpoint number in seconds.
+pub fn origin(checkpoint: i64, seq: i64) -> EventOrigin {
+    EventOrigin {
+        tx_digest: format!("tx{checkpoint}"),
+        event_seq: seq,
+        checkpoint,
+        checkpoint_timestamp_ms: checkpoint * 1000,
+    }
+}
+
+pub fn id(byte: u8) -> ObjectID {
+    ObjectID::from_single_byte(byte)
+}
+
+pub fn address(byte: u8) -> SuiAddress {
+    SuiAddress::from(id(byte))
+}
+
+/// Index one event the way a sequential pipeline does: decode it, write its
+/// staged rows, then commit it.
+pub async fn index<H: EventHandler>(
+    conn: &mut events::Connection<'_>,
+    handler: &H,
+    event: &H::Event,
+    origin: EventOrigin,
+) -> usize {
+    let contents = bcs::to_bytes(event).unwrap();
+    let value = handler.decode(&contents, &origin).unwrap();
+
+    let mut batch = InsertBatch::default();
+    H::stage(&value, &mut batch);
+
+    batch.write(conn).await.unwrap() + H::commit(&value, conn).await.unwrap()
+}
@@ -0,0 +1,406 @@
+//! Buy and sell offer events applied to a scratch database.
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use common::{address, id, index, origin, run, ScratchDb};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use events_indexer::events::automatic_buy::{AutomaticBuyEvent, AutomaticBuyHandler};
+use events_indexer::events::buy_offer_cancelled::{BuyOfferCancelledEvent, BuyOfferCancelledHandler};
+use events_indexer::events::buy_offer_created::{BuyOfferCreatedEvent, BuyOfferCreatedHandler};
+use events_indexer::events::buy_offer_deleted::{BuyOfferDeletedEvent, BuyOfferDeletedHandler};
+use events_indexer::events::buy_offer_modified::{BuyOfferModifiedEvent, BuyOfferModifiedHandler};
+use events_indexer::events::manual_buy::{ManualBuyEvent, ManualBuyHandler};
+use events_indexer::events::sell_offer_made::{SellOfferMadeEvent, SellOfferMadeHandler};
+use events_indexer::events::Connection;
+use events_indexer::jobs::buy_offer_expiry::BuyOfferExpiryJob;
+use events_indexer::jobs::{CheckpointJob, CheckpointTime};
+use events_indexer::models::{BuyOffer, SellOffer};
+use events_indexer::queries::{self, Page};
+use events_indexer::schema::{BuyOfferPriceChange, SellOfferRevision};
+
+const BUY_OFFER: u8 = 0x40;
+const OWNER: u8 = 0x30;
+
+fn buy_offer_created(price: u64) -> BuyOfferCreatedEvent {
+    BuyOfferCreatedEvent {
+        buy_offer_id: id(BUY_OFFER),
+        owner: address(OWNER),
+        product: "Headphones".to_string(),
+        price,
+        offer_type_is_time_based: false,
+        deadline: 0,
+        timestamp: 1,
+    }
+}
+
+/// Sell offer `sell_offer` by agent `agent` on the buy offer.
+fn sell_offer_made(sell_offer: u8, agent: u8, price: u64, is_update: bool) -> SellOfferMadeEvent {
+    SellOfferMadeEvent {
+        buy_offer_id: id(BUY_OFFER),
+        sell_offer_id: id(sell_offer),
+        agent_id: id(agent),
+        agent_address: address(agent),
+        store_link: format!("https://example.com/{sell_offer}"),
+        price,
+        is_update,
+    }
+}
+
+fn buy_offer_deleted(remaining_balance: u64) -> BuyOfferDeletedEvent {
+    BuyOfferDeletedEvent {
+        buy_offer_id: id(BUY_OFFER),
+        owner: address(OWNER),
+        remaining_balance,
+    }
+}
+
+async fn buy_offer(conn: &mut Connection<'_>) -> BuyOffer {
+    queries::buy_offer(conn, &id(BUY_OFFER).to_string()).await.unwrap().unwrap()
+}
+
+async fn sell_offer(conn: &mut Connection<'_>, sell_offer: u8) -> SellOffer {
+    queries::sell_offer(conn, &id(sell_offer).to_string()).await.unwrap().unwrap()
+}
+
+/// A buy offer at checkpoint 10 with two sell offers at checkpoint 20.
+async fn offers(conn: &mut Connection<'_>) {
+    index(conn, &BuyOfferCreatedHandler, &buy_offer_created(1_000), origin(10, 0)).await;
+    index(conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 900, false), origin(20, 0)).await;
+    index(conn, &SellOfferMadeHandler, &sell_offer_made(0x52, 0x12, 950, false), origin(20, 1)).await;
+}
+
+#[tokio::test]
+async fn cancelled_offers_are_closed_not_deleted() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    let cancelled = BuyOfferCancelledEvent {
+        buy_offer_id: id(BUY_OFFER),
+        owner: address(OWNER),
+        refunded_amount: 1_000,
+    };
+    index(&mut conn, &BuyOfferCancelledHandler, &cancelled, origin(30, 0)).await;
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(1_000), origin(30, 1)).await;
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!(offer.status, "cancelled");
+    assert_eq!(offer.remaining_balance, Some(BigDecimal::from(1_000)));
+    assert_eq!(offer.closed_at, Some(30));
+
+    for id in [0x51, 0x52] {
+        let sell_offer = sell_offer(&mut conn, id).await;
+        assert_eq!(sell_offer.status, "closed");
+        assert_eq!(sell_offer.closed_at, Some(30));
+    }
+}
+
+#[tokio::test]
+async fn deletion_alone_closes_by_remaining_balance() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    // Without `BuyOfferCancelled`, a refunded balance still means a cancellation.
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(1_000), origin(30, 0)).await;
+    assert_eq!(buy_offer(&mut conn).await.status, "cancelled");
+}
+
+#[tokio::test]
+async fn deletion_with_an_empty_balance_is_fulfilled() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(0), origin(30, 0)).await;
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!(offer.status, "fulfilled");
+    assert_eq!(offer.remaining_balance, Some(BigDecimal::from(0)));
+    assert_eq!(offer.closed_at, Some(30));
+}
+
+#[tokio::test]
+async fn sell_offers_made_after_the_deletion_are_left_open() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    // Replayed out of order: a row from a later checkpoint is already there.
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x53, 0x13, 800, false), origin(40, 0)).await;
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(0), origin(30, 0)).await;
+
+    assert_eq!(sell_offer(&mut conn, 0x51).await.status, "closed");
+    assert_eq!(sell_offer(&mut conn, 0x53).await.status, "open");
+}
+
+fn buy_offer_modified(old_price: u64, new_price: u64) -> BuyOfferModifiedEvent {
+    BuyOfferModifiedEvent {
+        buy_offer_id: id(BUY_OFFER),
+        owner: address(OWNER),
+        old_price,
+        new_price,
+        price_reduction: old_price - new_price,
+    }
+}
+
+#[tokio::test]
+async fn price_changes_are_recorded_and_update_the_offer() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    index(&mut conn, &BuyOfferModifiedHandler, &buy_offer_modified(1_000, 800), origin(30, 0)).await;
+    index(&mut conn, &BuyOfferModifiedHandler, &buy_offer_modified(800, 700), origin(40, 0)).await;
+
+    assert_eq!(buy_offer(&mut conn).await.price, BigDecimal::from(700));
+
+    let changes: Vec<(i64, BigDecimal)> = BuyOfferPriceChange::table
+        .select((BuyOfferPriceChange::checkpoint, BuyOfferPriceChange::new_price))
+        .order_by(BuyOfferPriceChange::checkpoint)
+        .load(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(changes, [(30, BigDecimal::from(800)), (40, BigDecimal::from(700))]);
+}
+
+#[tokio::test]
+async fn replaying_an_older_price_change_keeps_the_latest_price() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    index(&mut conn, &BuyOfferModifiedHandler, &buy_offer_modified(1_000, 800), origin(30, 0)).await;
+    index(&mut conn, &BuyOfferModifiedHandler, &buy_offer_modified(800, 700), origin(40, 0)).await;
+    index(&mut conn, &BuyOfferModifiedHandler, &buy_offer_modified(1_000, 800), origin(30, 0)).await;
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!(offer.price, BigDecimal::from(700));
+    assert_eq!(offer.updated_at, Some(40));
+}
+
+#[tokio::test]
+async fn revisions_update_the_sell_offer_in_place() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 850, true), origin(30, 0)).await;
+
+    let revised = sell_offer(&mut conn, 0x51).await;
+    assert_eq!(revised.price, BigDecimal::from(850));
+    assert!(revised.is_update);
+    assert_eq!(revised.updated_at, Some(30));
+    // The row keeps the provenance of the original offer.
+    assert_eq!(revised.checkpoint, Some(20));
+
+    let revisions: i64 = SellOfferRevision::table
+        .filter(SellOfferRevision::sell_offer_id.eq(id(0x51).to_string()))
+        .count()
+        .get_result(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(revisions, 2);
+}
+
+#[tokio::test]
+async fn replaying_an_older_revision_keeps_the_latest_one() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 850, true), origin(30, 0)).await;
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 800, true), origin(40, 0)).await;
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 850, true), origin(30, 0)).await;
+
+    let revised = sell_offer(&mut conn, 0x51).await;
+    assert_eq!(revised.price, BigDecimal::from(800));
+    assert_eq!(revised.updated_at, Some(40));
+}
+
+async fn statuses(conn: &mut Connection<'_>) -> Vec<(String, String)> {
+    let mut statuses: Vec<_> = queries::sell_offers_for_buy_offer(conn, &id(BUY_OFFER).to_string(), &Page::first(10))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| (s.sell_offer_id, s.status))
+        .collect();
+    statuses.sort();
+    statuses
+}
+
+fn sell_offer_statuses(expected: &[(u8, &str)]) -> Vec<(String, String)> {
+    expected.iter().map(|(sell_offer, status)| (id(*sell_offer).to_string(), status.to_string())).collect()
+}
+
+#[tokio::test]
+async fn manual_buys_fulfil_the_offer() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    let manual_buy = ManualBuyEvent {
+        buy_offer_id: id(BUY_OFFER),
+        buyer: address(OWNER),
+        agent_id: id(0x12),
+        sell_offer_id: id(0x52),
+        store_link: "https://example.com/82".to_string(),
+        product_price: 950,
+        agent_fee: 10,
+        total_paid: 960,
+    };
+    index(&mut conn, &ManualBuyHandler, &manual_buy, origin(30, 0)).await;
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(0), origin(30, 1)).await;
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!(offer.status, "fulfilled");
+    assert_eq!(offer.accepted_sell_offer_id, Some(id(0x52).to_string()));
+    assert_eq!(offer.total_paid, Some(BigDecimal::from(960)));
+    assert_eq!((offer.filled_at, offer.closed_at), (Some(30), Some(30)));
+
+    assert_eq!(statuses(&mut conn).await, sell_offer_statuses(&[(0x51, "rejected"), (0x52, "accepted")]));
+}
+
+fn automatic_buy(agent: u8, product_price: u64) -> AutomaticBuyEvent {
+    AutomaticBuyEvent {
+        buy_offer_id: id(BUY_OFFER),
+        buyer: address(OWNER),
+        agent_id: id(agent),
+        store_link: "https://example.com/item".to_string(),
+        product_price,
+        agent_fee: 20,
+        platform_fee: 5,
+        buyer_savings: 0,
+    }
+}
+
+#[tokio::test]
+async fn automatic_buys_fulfil_the_offer_like_manual_buys() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    // A sell offer below the price is bought in the same transaction.
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x53, 0x13, 800, false), origin(30, 0)).await;
+    index(&mut conn, &AutomaticBuyHandler, &automatic_buy(0x13, 800), origin(30, 1)).await;
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(0), origin(30, 2)).await;
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!(offer.status, "fulfilled");
+    assert_eq!(offer.accepted_sell_offer_id, Some(id(0x53).to_string()));
+    assert_eq!(offer.total_paid, Some(BigDecimal::from(825)));
+    assert_eq!((offer.filled_at, offer.closed_at), (Some(30), Some(30)));
+
+    assert_eq!(
+        statuses(&mut conn).await,
+        sell_offer_statuses(&[(0x51, "rejected"), (0x52, "rejected"), (0x53, "accepted")])
+    );
+}
+
+#[tokio::test]
+async fn service_buys_of_expired_offers_accept_one_sell_offer() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    // As `BuyOfferExpiry` leaves an expired offer.
+    run(
+        &mut conn,
+        r#"UPDATE "BuyOffer" SET status = 'expired', closed_at = 25;
+           UPDATE "SellOffer" SET status = 'closed', closed_at = 25"#,
+    )
+    .await;
+
+    index(&mut conn, &AutomaticBuyHandler, &automatic_buy(0x11, 900), origin(30, 1)).await;
+    index(&mut conn, &BuyOfferDeletedHandler, &buy_offer_deleted(0), origin(30, 2)).await;
+
+    assert_eq!(buy_offer(&mut conn).await.accepted_sell_offer_id, Some(id(0x51).to_string()));
+    assert_eq!(statuses(&mut conn).await, sell_offer_statuses(&[(0x51, "accepted"), (0x52, "rejected")]));
+}
+
+fn checkpoints(range: std::ops::RangeInclusive<i64>) -> Vec<CheckpointTime> {
+    range.map(|checkpoint| CheckpointTime { checkpoint, timestamp_ms: checkpoint * 1000 }).collect()
+}
+
+/// The offers of `offers`, with the buy offer due at checkpoint 35.
+async fn time_based_offers(conn: &mut Connection<'_>) {
+    let created = BuyOfferCreatedEvent {
+        offer_type_is_time_based: true,
+        deadline: 35_000,
+        ..buy_offer_created(1_000)
+    };
+    index(conn, &BuyOfferCreatedHandler, &created, origin(10, 0)).await;
+    index(conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 900, false), origin(20, 0)).await;
+    index(conn, &SellOfferMadeHandler, &sell_offer_made(0x52, 0x12, 950, false), origin(20, 1)).await;
+}
+
+#[tokio::test]
+async fn time_based_offers_expire_at_the_first_checkpoint_past_the_deadline() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    time_based_offers(&mut conn).await;
+
+    assert_eq!(BuyOfferExpiryJob.run(&checkpoints(30..=34), &mut conn).await.unwrap(), 0);
+    assert_eq!(buy_offer(&mut conn).await.status, "open");
+
+    // The closing checkpoint does not depend on where the run starts.
+    BuyOfferExpiryJob.run(&checkpoints(35..=40), &mut conn).await.unwrap();
+
+    let offer = buy_offer(&mut conn).await;
+    assert_eq!((offer.status.as_str(), offer.closed_at), ("expired", Some(35)));
+    assert_eq!(statuses(&mut conn).await, sell_offer_statuses(&[(0x51, "closed"), (0x52, "closed")]));
+    assert_eq!(sell_offer(&mut conn, 0x51).await.closed_at, Some(35));
+}
+
+#[tokio::test]
+async fn offers_that_are_not_time_based_never_expire() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    assert_eq!(BuyOfferExpiryJob.run(&checkpoints(30..=40), &mut conn).await.unwrap(), 0);
+    assert_eq!(buy_offer(&mut conn).await.status, "open");
+}
+
+#[tokio::test]
+async fn expiry_ignores_offers_created_after_the_run() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    // Already written by the batch when the job runs as of checkpoint 9.
+    time_based_offers(&mut conn).await;
+    let late = [CheckpointTime { checkpoint: 9, timestamp_ms: 40_000 }];
+
+    assert_eq!(BuyOfferExpiryJob.run(&late, &mut conn).await.unwrap(), 0);
+    assert_eq!(buy_offer(&mut conn).await.status, "open");
+}
+
+#[tokio::test]
+async fn sell_offers_on_unindexed_buy_offers_are_skipped() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    // As if `BuyOfferCreated` failed to decode: the foreign key would stop the
+    // pipeline when the transaction commits.
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 900, false), origin(20, 0)).await;
+    index(&mut conn, &SellOfferMadeHandler, &sell_offer_made(0x51, 0x11, 850, true), origin(21, 0)).await;
+
+    assert!(queries::sell_offer(&mut conn, &id(0x51).to_string()).await.unwrap().is_none());
+}
@@ -0,0 +1,140 @@
+//! Reads of `events_indexer::queries` against a scratch database.
+
+mod common;
+
+use common::{run, set_watermark, ScratchDb};
+use diesel_async::AsyncPgConnection;
+use events_indexer::queries::{self, Page, ShopPurchaseFilter};
+
+/// Insert a shop purchase for `agent_id` at `checkpoint`, or without
+/// provenance when `None`, as rows indexed before it was recorded.
+async fn shop_purchase(conn: &mut AsyncPgConnection, agent_id: &str, checkpoint: Option<i64>) {
+    let provenance = match checkpoint {
+        Some(checkpoint) => format!("'tx{checkpoint}', 0, {checkpoint}, {checkpoint}000"),
+        None => "NULL, NULL, NULL, NULL".to_string(),
+    };
+
+    run(
+        conn,
+        &format!(
+            r#"INSERT INTO "ShopPurchase"
+               (agent_id, store_link, product_price, agent_fee, platform_fee,
+                tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms)
+               VALUES ('{agent_id}', 'link', 100, 5, 1, {provenance})"#
+        ),
+    )
+    .await;
+}
+
+async fn manual_buy(conn: &mut AsyncPgConnection, buyer: &str, checkpoint: i64) {
+    run(
+        conn,
+        &format!(
+            r#"INSERT INTO "ManualBuy"
+               (buy_offer_id, buyer, agent_id, sell_offer_id, store_link, product_price, agent_fee,
+                total_paid, tx_digest, event_seq, checkpoint, checkpoint_timestamp_ms)
+               VALUES ('0xb', '{buyer}', '0xa', '0xs', 'link', 100, 5, 105,
+                       'tx{checkpoint}', 0, {checkpoint}, {checkpoint}000)"#
+        ),
+    )
+    .await;
+}
+
+fn checkpoints<T>(rows: &[T], checkpoint: impl Fn(&T) -> Option<i64>) -> Vec<Option<i64>> {
+    rows.iter().map(checkpoint).collect()
+}
+
+#[tokio::test]
+async fn shop_purchases_page_in_checkpoint_order_up_to_the_watermark() {
+    let Some(db) = ScratchDb::create() else { return };
+    let mut conn = db.connect().await;
+    let all = ShopPurchaseFilter::default();
+
+    // Committed out of order, as a concurrent pipeline does.
+    for checkpoint in [30, 10, 20, 40] {
+        shop_purchase(&mut conn, "0xa", Some(checkpoint)).await;
+    }
+
+    // Nothing is listed before the pipeline has committed anything.
+    let page = queries::shop_purchases(&mut conn, &all, &Page::first(10)).await.unwrap();
+    assert!(page.is_empty());
+
+    set_watermark(&mut conn, "shop_purchases", 30).await;
+
+    let first = queries::shop_purchases(&mut conn, &all, &Page::first(2)).await.unwrap();
+    assert_eq!(checkpoints(&first, |r| r.checkpoint), [Some(10), Some(20)]);
+
+    let after = first.last().unwrap().checkpoint_key();
+    let second = queries::shop_purchases(&mut conn, &all, &Page::after(after, 2)).await.unwrap();
+    assert_eq!(checkpoints(&second, |r| r.checkpoint), [Some(30)]);
+
+    // Checkpoint 35 is committed after 40, with a higher id, but still comes
+    // after the cursor rather than being skipped.
+    shop_purchase(&mut conn, "0xa", Some(35)).await;
+    set_watermark(&mut conn, "shop_purchases", 40).await;
+
+    let after = second.last().unwrap().checkpoint_key();
+    let third = queries::shop_purchases(&mut conn, &all, &Page::after(after, 2)).await.unwrap();
+    assert_eq!(checkpoints(&third, |r| r.checkpoint), [Some(35), Some(40)]);
+}
+
+#[tokio::test]
+async fn shop_purchases_without_provenance_come_first() {
+    let Some(db) = ScratchDb::create() else { return };
+    let mut conn = db.connect().await;
+    let all = ShopPurchaseFilter::default();
+
+    shop_purchase(&mut conn, "0xa", Some(10)).await;
+    shop_purchase(&mut conn, "0xa", None).await;
+    shop_purchase(&mut conn, "0xa", None).await;
+    set_watermark(&mut conn, "shop_purchases", 10).await;
+
+    let first = queries::shop_purchases(&mut conn, &all, &Page::first(1)).await.unwrap();
+    assert_eq!(checkpoints(&first, |r| r.checkpoint), [None]);
+    assert_eq!(first[0].checkpoint_key().0, -1);
+
+    let after = first[0].checkpoint_key();
+    let rest = queries::shop_purchases(&mut conn, &all, &Page::after(after, 10)).await.unwrap();
+    assert_eq!(checkpoints(&rest, |r| r.checkpoint), [None, Some(10)]);
+}
+
+#[tokio::test]
+async fn purchases_by_agent_only_lists_the_agent() {
+    let Some(db) = ScratchDb::create() else { return };
+    let mut conn = db.connect().await;
+
+    shop_purchase(&mut conn, "0xa", Some(10)).await;
+    shop_purchase(&mut conn, "0xb", Some(20)).await;
+    shop_purchase(&mut conn, "0xa", Some(30)).await;
+    set_watermark(&mut conn, "shop_purchases", 30).await;
+
+    let rows = queries::purchases_by_agent(&mut conn, "0xa", &Page::first(10)).await.unwrap();
+    assert_eq!(checkpoints(&rows, |r| r.checkpoint), [Some(10), Some(30)]);
+    assert!(rows.iter().all(|r| r.agent_id == "0xa"));
+}
+
+#[tokio::test]
+async fn manual_buys_stop_at_their_own_pipeline_watermark() {
+    let Some(db) = ScratchDb::create() else { return };
+    let mut conn = db.connect().await;
+
+    manual_buy(&mut conn, "0xu", 20).await;
+    manual_buy(&mut conn, "0xu", 10).await;
+    manual_buy(&mut conn, "0xv", 15).await;
+
+    // Another pipeline's watermark does not count.
+    set_watermark(&mut conn, "shop_purchases", 20).await;
+    set_watermark(&mut conn, "manual_buys", 15).await;
+
+    let rows = queries::manual_buys(&mut conn, &Default::default(), &Page::first(10))
+        .await
+        .unwrap();
+    assert_eq!(checkpoints(&rows, |r| r.checkpoint), [Some(10), Some(15)]);
+
+    let filter = queries::ManualBuyFilter {
+        buyer: Some("0xu".to_string()),
+        ..Default::default()
+    };
+    let rows = queries::manual_buys(&mut conn, &filter, &Page::first(10)).await.unwrap();
+    assert_eq!(checkpoints(&rows, |r| r.checkpoint), [Some(10)]);
+}
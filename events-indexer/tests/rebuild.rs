@@ -0,0 +1,115 @@
+//! Rebuilding a table from the `RawEvent` archive of a scratch database.
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use common::{address, id, index, origin, run, ScratchDb};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Bytea, Text};
+use diesel_async::RunQueryDsl;
+use events_indexer::events::buy_offer_created::{BuyOfferCreatedEvent, BuyOfferCreatedHandler};
+use events_indexer::events::sell_offer_made::{SellOfferMadeEvent, SellOfferMadeHandler};
+use events_indexer::events::{Connection, EventHandler, EventOrigin, EventRegistry};
+use events_indexer::rebuild::{hold_indexer_lock, rebuild};
+use events_indexer::schema::{SellOffer, SellOfferRevision};
+
+/// Index `event` and archive it, as the pipelines do.
+async fn index_and_archive<H: EventHandler>(
+    conn: &mut Connection<'_>,
+    handler: &H,
+    event: &H::Event,
+    origin: EventOrigin,
+) {
+    diesel::sql_query(
+        r#"INSERT INTO "RawEvent"
+           (package_id, module, struct_name, event_type, tx_digest, event_seq, sender,
+            checkpoint, checkpoint_timestamp_ms, bcs)
+           VALUES ('0x1', 'events', $1, '0x1::events::' || $1, $2, $3, '0x2', $4, $5, $6)"#,
+    )
+    .bind::<Text, _>(H::NAME)
+    .bind::<Text, _>(&origin.tx_digest)
+    .bind::<BigInt, _>(origin.event_seq)
+    .bind::<BigInt, _>(origin.checkpoint)
+    .bind::<BigInt, _>(origin.checkpoint_timestamp_ms)
+    .bind::<Bytea, _>(bcs::to_bytes(event).unwrap())
+    .execute(conn)
+    .await
+    .unwrap();
+
+    index(conn, handler, event, origin).await;
+}
+
+async fn offers(conn: &mut Connection<'_>) {
+    let created = BuyOfferCreatedEvent {
+        buy_offer_id: id(0x40),
+        owner: address(0x30),
+        product: "Headphones".to_string(),
+        price: 1_000,
+        offer_type_is_time_based: false,
+        deadline: 0,
+        timestamp: 1,
+    };
+    index_and_archive(conn, &BuyOfferCreatedHandler, &created, origin(10, 0)).await;
+
+    let made = SellOfferMadeEvent {
+        buy_offer_id: id(0x40),
+        sell_offer_id: id(0x51),
+        agent_id: id(0x11),
+        agent_address: address(0x11),
+        store_link: "https://example.com/item".to_string(),
+        price: 900,
+        is_update: false,
+    };
+    index_and_archive(conn, &SellOfferMadeHandler, &made, origin(20, 0)).await;
+}
+
+async fn count(conn: &mut Connection<'_>, table: &str) -> i64 {
+    #[derive(QueryableByName)]
+    struct Count {
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    let row: Count = diesel::sql_query(format!(r#"SELECT COUNT(*) AS count FROM "{table}""#))
+        .get_result(conn)
+        .await
+        .unwrap();
+    row.count
+}
+
+#[tokio::test]
+async fn rebuilds_only_insert_into_the_rebuilt_table() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    // `SellOfferMade` also records a revision, which must not come back.
+    diesel::delete(SellOfferRevision::table).execute(&mut conn).await.unwrap();
+    run(&mut conn, r#"UPDATE "SellOffer" SET price = 1"#).await;
+
+    let report = rebuild(&db, EventRegistry::priceless(), "SellOffer", false).await.unwrap();
+    assert_eq!((report.rows_before, report.rows_after, report.events_replayed), (1, 1, 1));
+
+    let price: BigDecimal = SellOffer::table.select(SellOffer::price).get_result(&mut conn).await.unwrap();
+    assert_eq!(price, BigDecimal::from(900));
+    assert_eq!(count(&mut conn, "SellOfferRevision").await, 0);
+}
+
+#[tokio::test]
+async fn rebuilds_are_refused_while_an_indexer_runs() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+    offers(&mut conn).await;
+
+    let mut indexer = db.connect().await.unwrap();
+    hold_indexer_lock(&mut indexer).await.unwrap();
+
+    let err = rebuild(&db, EventRegistry::priceless(), "SellOffer", false).await.unwrap_err();
+    assert!(format!("{err:#}").contains("stop it before rebuilding"), "{err:#}");
+    assert_eq!(count(&mut conn, "SellOffer").await, 1);
+
+    drop(indexer);
+    rebuild(&db, EventRegistry::priceless(), "SellOffer", false).await.unwrap();
+}
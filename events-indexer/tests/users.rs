@@ -0,0 +1,52 @@
+//! User registration and subscription expiry against a scratch database.
+
+mod common;
+
+use common::{address, id, index, origin, ScratchDb};
+use events_indexer::events::user_registered::{UserRegisteredEvent, UserRegisteredHandler};
+use events_indexer::events::Connection;
+use events_indexer::jobs::user_expiry::UserExpiryJob;
+use events_indexer::jobs::{CheckpointJob, CheckpointTime};
+use events_indexer::queries;
+
+/// User `owner` registered at `timestamp` with a subscription until `deadline`.
+fn user_registered(owner: u8, timestamp: u64, deadline: u64) -> UserRegisteredEvent {
+    UserRegisteredEvent {
+        user_id: id(owner),
+        user_object_address: address(owner + 0x10),
+        user_owner_address: address(owner),
+        subscription_fee: 10,
+        subscription_deadline: deadline,
+        timestamp,
+    }
+}
+
+async fn active(conn: &mut Connection<'_>, owner: u8) -> bool {
+    queries::user_by_owner_address(conn, &address(owner).to_string())
+        .await
+        .unwrap()
+        .unwrap()
+        .active
+}
+
+async fn expire(conn: &mut Connection<'_>, checkpoint: i64) -> usize {
+    let time = CheckpointTime { checkpoint, timestamp_ms: checkpoint * 1000 };
+    UserExpiryJob.run(&[time], conn).await.unwrap()
+}
+
+#[tokio::test]
+async fn subscriptions_expire_at_their_deadline() {
+    let Some(scratch) = ScratchDb::create() else { return };
+    let db = scratch.db().await;
+    let mut conn = db.connect().await.unwrap();
+
+    index(&mut conn, &UserRegisteredHandler, &user_registered(0x01, 10_000, 50_000), origin(10, 0)).await;
+    assert!(active(&mut conn, 0x01).await);
+
+    assert_eq!(expire(&mut conn, 49).await, 0);
+    assert!(active(&mut conn, 0x01).await);
+
+    // Valid while `timestamp < subscription_deadline`.
+    assert_eq!(expire(&mut conn, 50).await, 1);
+    assert!(!active(&mut conn, 0x01).await);
+}